@@ -0,0 +1,12 @@
+-- snapshots of the vocabulary counts, taken after each sync or external change
+CREATE TABLE vocab_history (
+    id integer primary key,
+    time integer not null,
+    words_known integer not null,
+    words_active integer not null,
+    words_inactive integer not null,
+    chars_known integer not null,
+    chars_active integer not null,
+    chars_inactive integer not null
+);
+CREATE INDEX vocab_history_time_index ON vocab_history(time);
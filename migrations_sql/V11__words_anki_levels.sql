@@ -0,0 +1,4 @@
+-- Anki word status becomes the level of the word's most advanced card
+-- (0=new, 1=learning, 2=young, 3=mature, 4=suspended),
+-- previously active words count as young and inactive ones as suspended until the next sync
+UPDATE words_anki SET status = CASE status WHEN 0 THEN 2 ELSE 4 END;
@@ -0,0 +1,6 @@
+-- words never to show again in word lists, across all books
+CREATE TABLE words_ignored (
+    word text primary key not null,
+    added_at integer not null,
+    source text not null
+);
@@ -0,0 +1,3 @@
+-- latest modification of the Anki notes (or their cards) a word was extracted from,
+-- in seconds since epoch, unknown until the next sync
+ALTER TABLE words_anki ADD COLUMN last_modified integer;
@@ -0,0 +1,7 @@
+-- characters explicitly marked as known (1) or unknown (0),
+-- overriding the characters derived from known words
+CREATE TABLE char_overrides (
+    hanzi text primary key not null,
+    known integer not null,
+    added_at integer not null
+);
@@ -0,0 +1,21 @@
+-- levels of several sources (e.g HSK 2.0 and 3.0) can coexist, one source is active
+CREATE TABLE level_sources (
+    source text primary key not null,
+    active integer not null
+);
+INSERT INTO level_sources (source, active)
+SELECT DISTINCT source, 0 FROM word_levels;
+-- previously the last import won, keep the source of the most recently inserted level active
+UPDATE level_sources SET active = 1
+WHERE source = (SELECT source FROM word_levels ORDER BY rowid DESC LIMIT 1);
+
+CREATE TABLE word_levels_by_source (
+    word text not null,
+    level integer not null,
+    source text not null,
+    primary key (word, source)
+);
+INSERT INTO word_levels_by_source (word, level, source)
+SELECT word, level, source FROM word_levels;
+DROP TABLE word_levels;
+ALTER TABLE word_levels_by_source RENAME TO word_levels;
@@ -0,0 +1,3 @@
+-- hash of the segmented book, the key of its analysis cache entries,
+-- computed at import (null for books stored before, filled in when they are first loaded)
+ALTER TABLE books ADD COLUMN content_hash text;
@@ -0,0 +1,9 @@
+-- words per status and the known levels in effect, so that snapshots stay comparable
+-- when the known levels change (null for snapshots taken before)
+ALTER TABLE vocab_history ADD COLUMN words_suspended integer;
+ALTER TABLE vocab_history ADD COLUMN words_new integer;
+ALTER TABLE vocab_history ADD COLUMN words_learning integer;
+ALTER TABLE vocab_history ADD COLUMN words_young integer;
+ALTER TABLE vocab_history ADD COLUMN words_mature integer;
+ALTER TABLE vocab_history ADD COLUMN words_external integer;
+ALTER TABLE vocab_history ADD COLUMN known_levels text;
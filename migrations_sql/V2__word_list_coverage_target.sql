@@ -0,0 +1,2 @@
+-- word lists created from a coverage plan record the targeted word coverage in percent
+ALTER TABLE word_lists ADD COLUMN coverage_target integer;
@@ -0,0 +1,2 @@
+-- word lists created from the unknown characters of a book (one word per character)
+ALTER TABLE word_lists ADD COLUMN character_focused integer not null default 0;
@@ -0,0 +1,15 @@
+-- word levels (e.g HSK) imported from local level lists
+CREATE TABLE word_levels (
+    word text primary key,
+    level integer not null,
+    source text not null
+);
+
+-- general frequency ranks imported from a local frequency list (e.g SUBTLEX-CH)
+CREATE TABLE word_ranks (
+    word text primary key,
+    rank integer not null
+);
+
+-- word lists can be restricted to words up to a level
+ALTER TABLE word_lists ADD COLUMN max_level integer;
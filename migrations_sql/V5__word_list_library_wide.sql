@@ -0,0 +1,2 @@
+-- word lists created from the whole library instead of a single book
+ALTER TABLE word_lists ADD COLUMN library_wide integer not null default 0;
@@ -0,0 +1,2 @@
+-- word lists without unknown words that consist of known characters only
+ALTER TABLE word_lists ADD COLUMN exclude_guessable integer not null default 0;
@@ -0,0 +1,2 @@
+-- filter expression the word list was created with
+ALTER TABLE word_lists ADD COLUMN filter text;
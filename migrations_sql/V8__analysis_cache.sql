@@ -0,0 +1,14 @@
+-- version of the known vocabulary, increased whenever it changes
+CREATE TABLE vocab_version (
+    version integer not null
+);
+INSERT INTO vocab_version (version) VALUES (0);
+
+-- per-book stats and query results, only valid for the vocabulary version they were computed with
+CREATE TABLE analysis_cache (
+    book_hash text not null,
+    cache_key text not null,
+    vocab_version integer not null,
+    value_json text not null,
+    PRIMARY KEY ( book_hash, cache_key )
+);
@@ -0,0 +1,6 @@
+-- when and from where (file, manual, word list) external words were added,
+-- words added before this have no time and an unknown source
+ALTER TABLE words_external ADD COLUMN added_at integer;
+ALTER TABLE words_external ADD COLUMN source text not null default 'unknown';
+ALTER TABLE words_external ADD COLUMN note text;
+CREATE INDEX words_external_source_index ON words_external(source);
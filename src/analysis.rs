@@ -1,28 +1,76 @@
 use crate::{
+    db::vocab::{describe_known_levels, VocabStatus},
     extraction::{word_to_hanzi, ExtractionItem, ExtractionResult},
-    vocabulary::get_known_chars,
+    filter::FilterExpr,
+    vocabulary::{get_known_chars, CharOverrides},
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
+    ops::Range,
 };
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug)]
 pub struct AnalysisQuery {
     pub min_occurrence_words: u64,
     pub min_occurrence_unknown_chars: Option<u64>,
+    /// if Some(level), only include words with at most this level (e.g HSK)
+    pub max_level: Option<u8>,
+    /// whether to drop unknown words whose characters are all known
+    pub exclude_guessable: bool,
+    /// if Some(filter), only include words matching the filter expression
+    pub filter: Option<FilterExpr>,
+}
+
+impl AnalysisQuery {
+    /// Query for words occurring at least min_occurrence_words times, without further conditions
+    pub fn new(min_occurrence_words: u64) -> Self {
+        Self {
+            min_occurrence_words,
+            min_occurrence_unknown_chars: None,
+            max_level: None,
+            exclude_guessable: false,
+            filter: None,
+        }
+    }
+
+    /// Identifies the query in the analysis cache, together with the levels counting as known
+    /// and whether ignored words are left out of the unknown words
+    ///
+    /// unlike Display including the filter's source
+    pub fn cache_key(&self, known_levels: &[VocabStatus], exclude_ignored: bool) -> String {
+        let mut levels = describe_known_levels(known_levels);
+        if exclude_ignored {
+            levels.push_str(" -ignored");
+        }
+        match &self.filter {
+            Some(filter) => format!("{} {} {}", levels, self, filter.source()),
+            None => format!("{} {}", levels, self),
+        }
+    }
 }
 
 impl Display for AnalysisQuery {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.min_occurrence_unknown_chars {
-            Some(c) => write!(f, "w{}-c{}", self.min_occurrence_words, c),
-            None => write!(f, "w{}", self.min_occurrence_words),
+            Some(c) => write!(f, "w{}-c{}", self.min_occurrence_words, c)?,
+            None => write!(f, "w{}", self.min_occurrence_words)?,
         }
+        if let Some(level) = self.max_level {
+            write!(f, "-l{}", level)?;
+        }
+        if self.exclude_guessable {
+            write!(f, "-ng")?;
+        }
+        if self.filter.is_some() {
+            write!(f, "-f")?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct AnalysisInfo {
     pub total_words: u64,
     pub total_chars: u64,
@@ -32,17 +80,51 @@ pub struct AnalysisInfo {
     pub unknown_total_chars: u64,
     pub unknown_unique_words: u64,
     pub unknown_unique_chars: u64,
+    /// unknown words consisting only of known characters
+    pub guessable_total_words: u64,
+    pub guessable_unique_words: u64,
 }
 
-/// Get all items that fulfill the min occurrence conditions
+impl AnalysisInfo {
+    /// Total occurrences of unknown words that contain at least one unknown character
+    pub fn new_char_total_words(&self) -> u64 {
+        self.unknown_total_words - self.guessable_total_words
+    }
+
+    /// Unknown words that contain at least one unknown character
+    pub fn new_char_unique_words(&self) -> u64 {
+        self.unknown_unique_words - self.guessable_unique_words
+    }
+}
+
+/// Whether word is unknown but all of its characters are known
+pub fn is_guessable(
+    word: &str,
+    known_words: &HashSet<String>,
+    known_chars: &HashSet<String>,
+) -> bool {
+    !known_words.contains(word)
+        && word_to_hanzi(word)
+            .iter()
+            .all(|hanzi| known_chars.contains(*hanzi))
+}
+
+/// Get all items that fulfill the min occurrence and level conditions
 #[allow(clippy::unnecessary_unwrap)]
 pub fn get_filtered_extraction_items<'a>(
     extraction_res: &'a ExtractionResult,
-    min_occurrence_words: u64,
+    query: &AnalysisQuery,
     known_words: &HashSet<String>,
-    min_occurrence_unknown_chars: Option<u64>,
+    char_overrides: &CharOverrides,
 ) -> HashSet<&'a ExtractionItem> {
-    let known_chars = get_known_chars(known_words);
+    let AnalysisQuery {
+        min_occurrence_words,
+        min_occurrence_unknown_chars,
+        max_level,
+        exclude_guessable,
+        ref filter,
+    } = *query;
+    let known_chars = get_known_chars(known_words, char_overrides);
     let all_char_frequencies =
         ext_item_set_to_char_freq(&extraction_res.vocabulary.iter().collect());
     let unknown_char_frequencies: HashMap<&str, u64> = all_char_frequencies
@@ -63,32 +145,47 @@ pub fn get_filtered_extraction_items<'a>(
             min_occurring_words
         }
     };
+    // known words are kept regardless of their level so that totals stay complete
+    let level_condition = |extraction_item: &ExtractionItem| match max_level {
+        Some(max_level) => {
+            known_words.contains(&extraction_item.word)
+                || extraction_item
+                    .level
+                    .level
+                    .is_some_and(|level| level <= max_level)
+        }
+        None => true,
+    };
     extraction_res
         .vocabulary
         .iter()
-        .filter(|item| occurrence_condition(item))
+        .filter(|item| occurrence_condition(item) && level_condition(item))
+        .filter(|item| !exclude_guessable || !is_guessable(&item.word, known_words, &known_chars))
+        .filter(|item| filter.as_ref().is_none_or(|filter| filter.matches(item)))
         .collect()
 }
 
 /// Get analysis info about words/chars for raw extraction result
 ///
-/// min_occurrence_words: the minimum frequency for a word to be included in analysis
-/// min_occurrence_unknown_chars:
+/// query.min_occurrence_words: the minimum frequency for a word to be included in analysis
+/// query.min_occurrence_unknown_chars:
 ///     if Some(amount), also include all words that include a character
 ///     that overall occurrs at least this amount and is unknown
+/// query.max_level: if Some(level), only include unknown words up to this level
+/// query.exclude_guessable: whether to leave out unknown words made of known characters
+/// query.filter: if Some(filter), only include words matching the filter expression
+/// ignored_words: if Some(words), these are not counted as unknown words
+/// char_overrides: characters explicitly marked as known/unknown
 pub fn get_analysis_info(
     extraction_res: &ExtractionResult,
-    min_occurrence_words: u64,
+    query: &AnalysisQuery,
     known_words: &HashSet<String>,
-    min_occurrence_unknown_chars: Option<u64>,
+    ignored_words: Option<&HashSet<String>>,
+    char_overrides: &CharOverrides,
 ) -> AnalysisInfo {
-    let known_chars = get_known_chars(known_words);
-    let vocabulary_min_occurring = get_filtered_extraction_items(
-        extraction_res,
-        min_occurrence_words,
-        known_words,
-        min_occurrence_unknown_chars,
-    );
+    let known_chars = get_known_chars(known_words, char_overrides);
+    let vocabulary_min_occurring =
+        get_filtered_extraction_items(extraction_res, query, known_words, char_overrides);
     let total_words: u64 = vocabulary_min_occurring
         .iter()
         .map(|item| item.frequency)
@@ -104,6 +201,7 @@ pub fn get_analysis_info(
         .iter()
         .copied()
         .filter(|item| !known_words.contains(&item.word))
+        .filter(|item| ignored_words.is_none_or(|ignored| !ignored.contains(&item.word)))
         .collect();
     let unknown_total_words: u64 = unknown_voc_min_occ.iter().map(|item| item.frequency).sum();
     let unknown_char_min_occur: HashMap<&String, u64> = char_freq_min_occur
@@ -114,6 +212,12 @@ pub fn get_analysis_info(
     let unknown_total_chars: u64 = unknown_char_min_occur.values().sum();
     let unknown_unique_words = unknown_voc_min_occ.len() as u64;
     let unknown_unique_chars = unknown_char_min_occur.len() as u64;
+    let guessable: Vec<&&ExtractionItem> = unknown_voc_min_occ
+        .iter()
+        .filter(|item| is_guessable(&item.word, known_words, &known_chars))
+        .collect();
+    let guessable_total_words = guessable.iter().map(|item| item.frequency).sum();
+    let guessable_unique_words = guessable.len() as u64;
 
     AnalysisInfo {
         total_words,
@@ -124,7 +228,251 @@ pub fn get_analysis_info(
         unknown_total_chars,
         unknown_unique_words,
         unknown_unique_chars,
+        guessable_total_words,
+        guessable_unique_words,
+    }
+}
+
+/// A word to learn and the share of known words in the text after learning it
+/// and all words before it
+#[derive(Clone)]
+pub struct CoverageStep {
+    pub word: String,
+    pub frequency: u64,
+    pub cumulative_coverage: f64,
+}
+
+/// Share of word occurrences in the text that are known
+pub fn get_word_coverage(extraction_res: &ExtractionResult, known_words: &HashSet<String>) -> f64 {
+    let total: u64 = extraction_res
+        .vocabulary
+        .iter()
+        .map(|item| item.frequency)
+        .sum();
+    let known: u64 = extraction_res
+        .vocabulary
+        .iter()
+        .filter(|item| known_words.contains(&item.word))
+        .map(|item| item.frequency)
+        .sum();
+    if total == 0 {
+        return 1.0;
     }
+    known as f64 / total as f64
+}
+
+/// Get the smallest list of unknown words that lifts word coverage to the target (0.0 - 1.0)
+///
+/// greedily picks the most frequent unknown words, empty if target is already met
+pub fn get_coverage_plan(
+    extraction_res: &ExtractionResult,
+    known_words: &HashSet<String>,
+    target_coverage: f64,
+) -> Vec<CoverageStep> {
+    let total: u64 = extraction_res
+        .vocabulary
+        .iter()
+        .map(|item| item.frequency)
+        .sum();
+    if total == 0 {
+        return vec![];
+    }
+    let mut unknown: Vec<&ExtractionItem> = extraction_res
+        .vocabulary
+        .iter()
+        .filter(|item| !known_words.contains(&item.word))
+        .collect();
+    unknown.sort_by(|a, b| b.frequency.cmp(&a.frequency).then(a.word.cmp(&b.word)));
+    let mut covered = total - unknown.iter().map(|item| item.frequency).sum::<u64>();
+    let mut plan = Vec::new();
+    for item in unknown {
+        if covered as f64 / total as f64 >= target_coverage {
+            break;
+        }
+        covered += item.frequency;
+        plan.push(CoverageStep {
+            word: item.word.clone(),
+            frequency: item.frequency,
+            cumulative_coverage: covered as f64 / total as f64,
+        });
+    }
+    plan
+}
+
+/// Difficulty of a single chapter
+pub struct ChapterStats {
+    pub title: String,
+    pub total_words: u64,
+    pub known_words: u64,
+    /// unknown words that occur for the first time in this chapter
+    pub new_unknown_words: u64,
+}
+
+impl ChapterStats {
+    /// Share of word occurrences in the chapter that are known
+    pub fn comprehension(&self) -> f64 {
+        if self.total_words == 0 {
+            return 1.0;
+        }
+        self.known_words as f64 / self.total_words as f64
+    }
+}
+
+/// Get comprehension and amount of new unknown words for every chapter
+pub fn get_chapter_stats(
+    extraction_res: &ExtractionResult,
+    known_words: &HashSet<String>,
+) -> Vec<ChapterStats> {
+    let mut stats: Vec<ChapterStats> = extraction_res
+        .segmented_book
+        .chapter_cuts
+        .iter()
+        .map(|chapter| ChapterStats {
+            title: chapter.title.clone(),
+            total_words: 0,
+            known_words: 0,
+            new_unknown_words: 0,
+        })
+        .collect();
+    for item in &extraction_res.vocabulary {
+        let known = known_words.contains(&item.word);
+        for (chapter, frequency) in stats.iter_mut().zip(&item.chapter_frequencies) {
+            chapter.total_words += frequency;
+            if known {
+                chapter.known_words += frequency;
+            }
+        }
+        if !known {
+            if let Some(first) = item.chapter_frequencies.iter().position(|f| *f > 0) {
+                stats[first].new_unknown_words += 1;
+            }
+        }
+    }
+    stats
+}
+
+/// An unknown word occurring in both compared books
+pub struct SharedWord {
+    pub word: String,
+    pub frequency_a: u64,
+    pub frequency_b: u64,
+}
+
+/// Overlap of the unknown vocabulary of two books
+pub struct BookComparison {
+    /// most frequent (in both books combined) first
+    pub shared_unknown: Vec<SharedWord>,
+    pub unique_unknown_a: usize,
+    pub unique_unknown_b: usize,
+    pub coverage_a: f64,
+    pub coverage_b: f64,
+    /// word coverage of one book after learning all unknown words of the other
+    pub coverage_a_after_b: f64,
+    pub coverage_b_after_a: f64,
+}
+
+pub fn compare_books(
+    a: &ExtractionResult,
+    b: &ExtractionResult,
+    known_words: &HashSet<String>,
+) -> BookComparison {
+    let unknown = |res: &ExtractionResult| -> HashMap<String, u64> {
+        res.vocabulary
+            .iter()
+            .filter(|item| !known_words.contains(&item.word))
+            .map(|item| (item.word.clone(), item.frequency))
+            .collect()
+    };
+    let unknown_a = unknown(a);
+    let unknown_b = unknown(b);
+    let mut shared_unknown: Vec<SharedWord> = unknown_a
+        .iter()
+        .filter_map(|(word, frequency_a)| {
+            unknown_b.get(word).map(|frequency_b| SharedWord {
+                word: word.clone(),
+                frequency_a: *frequency_a,
+                frequency_b: *frequency_b,
+            })
+        })
+        .collect();
+    shared_unknown.sort_by(|x, y| {
+        (y.frequency_a + y.frequency_b)
+            .cmp(&(x.frequency_a + x.frequency_b))
+            .then(x.word.cmp(&y.word))
+    });
+    let known_after = |learned: &HashMap<String, u64>| -> HashSet<String> {
+        known_words
+            .iter()
+            .cloned()
+            .chain(learned.keys().cloned())
+            .collect()
+    };
+    BookComparison {
+        unique_unknown_a: unknown_a.len() - shared_unknown.len(),
+        unique_unknown_b: unknown_b.len() - shared_unknown.len(),
+        shared_unknown,
+        coverage_a: get_word_coverage(a, known_words),
+        coverage_b: get_word_coverage(b, known_words),
+        coverage_a_after_b: get_word_coverage(a, &known_after(&unknown_b)),
+        coverage_b_after_a: get_word_coverage(b, &known_after(&unknown_a)),
+    }
+}
+
+/// An unknown character, its occurrences and the words it occurs in
+pub struct UnknownCharInfo {
+    pub hanzi: String,
+    pub frequency: u64,
+    /// (word, frequency) of all words containing the character, most frequent first
+    pub words: Vec<(String, u64)>,
+}
+
+/// Get all unknown characters of the text, most frequent first
+pub fn get_unknown_chars(
+    extraction_res: &ExtractionResult,
+    known_words: &HashSet<String>,
+    char_overrides: &CharOverrides,
+) -> Vec<UnknownCharInfo> {
+    let known_chars = get_known_chars(known_words, char_overrides);
+    let mut unknown_chars: HashMap<&str, UnknownCharInfo> = HashMap::new();
+    for item in &extraction_res.vocabulary {
+        let mut hanzis = word_to_hanzi(&item.word);
+        hanzis.sort();
+        hanzis.dedup();
+        for hanzi in hanzis {
+            if known_chars.contains(hanzi) {
+                continue;
+            }
+            let info = unknown_chars
+                .entry(hanzi)
+                .or_insert_with(|| UnknownCharInfo {
+                    hanzi: hanzi.to_string(),
+                    frequency: 0,
+                    words: vec![],
+                });
+            info.frequency += item.frequency;
+            info.words.push((item.word.clone(), item.frequency));
+        }
+    }
+    let mut unknown_chars: Vec<UnknownCharInfo> = unknown_chars.into_values().collect();
+    for info in unknown_chars.iter_mut() {
+        info.words
+            .sort_by(|(w1, f1), (w2, f2)| f2.cmp(f1).then(w1.cmp(w2)));
+    }
+    unknown_chars.sort_by(|a, b| b.frequency.cmp(&a.frequency).then(a.hanzi.cmp(&b.hanzi)));
+    unknown_chars
+}
+
+/// Sort items by their occurrences within the given chapters (descending),
+/// ties are broken by total frequency
+pub fn sort_by_local_frequency(items: &mut [&ExtractionItem], chapters: Range<usize>) {
+    items.sort_by(|a, b| {
+        let local_a = a.frequency_in_chapters(chapters.clone());
+        let local_b = b.frequency_in_chapters(chapters.clone());
+        local_b
+            .cmp(&local_a)
+            .then(b.frequency.cmp(&a.frequency))
+            .then(a.word.cmp(&b.word))
+    });
 }
 
 fn ext_item_set_to_char_freq(ext_items: &HashSet<&ExtractionItem>) -> HashMap<String, u64> {
@@ -144,3 +492,191 @@ fn ext_item_set_to_char_freq(ext_items: &HashSet<&ExtractionItem>) -> HashMap<St
         });
     char_freq_map
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::analysis::{
+        compare_books, get_analysis_info, get_chapter_stats, get_coverage_plan,
+        get_filtered_extraction_items, get_unknown_chars, AnalysisQuery,
+    };
+    use crate::extraction::extract_vocab_from_segmented;
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+    use crate::vocabulary::{get_known_words_and_chars, CharOverrides};
+
+    #[test]
+    fn coverage_plan_picks_most_frequent_unknown_words() {
+        let cut: Vec<String> = ["我", "我", "我", "猫", "猫", "狗", "鱼", "我", "猫", "鸟"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut,
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let known = HashSet::from(["我".to_string()]);
+        let plan = get_coverage_plan(&extraction_res, &known, 0.8);
+        let words: Vec<&str> = plan.iter().map(|step| step.word.as_str()).collect();
+        assert_eq!(words, vec!["猫", "狗"]);
+        assert!((plan[0].cumulative_coverage - 0.7).abs() < 1e-9);
+        assert!((plan[1].cumulative_coverage - 0.8).abs() < 1e-9);
+        assert!(get_coverage_plan(&extraction_res, &known, 0.4).is_empty());
+    }
+
+    #[test]
+    fn split_unknown_words_by_guessability() {
+        let cut: Vec<String> = ["火车", "站", "火车站", "火车站", "飞机"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut,
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let known = HashSet::from(["火车".to_string(), "站".to_string()]);
+        let no_overrides = CharOverrides::default();
+        let mut query = AnalysisQuery::new(1);
+        let info = get_analysis_info(&extraction_res, &query, &known, None, &no_overrides);
+        assert_eq!(info.unknown_unique_words, 2);
+        assert_eq!(info.guessable_unique_words, 1);
+        assert_eq!(info.guessable_total_words, 2);
+        assert_eq!(info.new_char_unique_words(), 1);
+        let ignored = HashSet::from(["飞机".to_string()]);
+        let info = get_analysis_info(
+            &extraction_res,
+            &query,
+            &known,
+            Some(&ignored),
+            &no_overrides,
+        );
+        assert_eq!(info.unknown_unique_words, 1);
+        assert_eq!(info.new_char_unique_words(), 0);
+        assert_eq!(info.unique_words, 4);
+        query.exclude_guessable = true;
+        let info = get_analysis_info(&extraction_res, &query, &known, None, &no_overrides);
+        assert_eq!(info.unknown_unique_words, 1);
+        assert_eq!(info.guessable_unique_words, 0);
+    }
+
+    #[test]
+    fn unknown_chars_by_frequency_with_words() {
+        let cut: Vec<String> = ["火车", "汽车", "汽车", "火", "电车", "我"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut,
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let known = HashSet::from(["火".to_string(), "我".to_string()]);
+        let unknown_chars = get_unknown_chars(&extraction_res, &known, &CharOverrides::default());
+        let chars: Vec<(&str, u64)> = unknown_chars
+            .iter()
+            .map(|info| (info.hanzi.as_str(), info.frequency))
+            .collect();
+        assert_eq!(chars, vec![("车", 4), ("汽", 2), ("电", 1)]);
+        assert_eq!(
+            unknown_chars[0].words,
+            vec![
+                ("汽车".to_string(), 2),
+                ("火车".to_string(), 1),
+                ("电车".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn char_overrides_change_guessability() {
+        let cut: Vec<String> = ["火车", "火车站", "飞机"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut,
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let char_overrides = CharOverrides {
+            known: HashSet::from(["飞".to_string(), "机".to_string(), "站".to_string()]),
+            unknown: HashSet::from(["车".to_string()]),
+        };
+        let known = get_known_words_and_chars(HashSet::from(["火车".to_string()]), &char_overrides);
+        assert!(!known.contains("车"));
+        let mut query = AnalysisQuery::new(1);
+        query.exclude_guessable = true;
+        let items = get_filtered_extraction_items(&extraction_res, &query, &known, &char_overrides);
+        let words: HashSet<&str> = items.iter().map(|item| item.word.as_str()).collect();
+        assert_eq!(words, HashSet::from(["火车", "火车站"]));
+        let unknown_chars = get_unknown_chars(&extraction_res, &known, &char_overrides);
+        assert_eq!(unknown_chars.len(), 1);
+        assert_eq!(unknown_chars[0].hanzi, "车");
+    }
+
+    #[test]
+    fn compare_unknown_words_of_two_books() {
+        let extract = |words: &[&str]| {
+            extract_vocab_from_segmented(BookSegmentation {
+                title: "书".to_string(),
+                chapter_cuts: vec![ChapterSegmentation {
+                    title: "一".to_string(),
+                    cut: words.iter().map(|w| w.to_string()).collect(),
+                    sentences: vec![],
+                }],
+            })
+        };
+        let a = extract(&["我", "猫", "猫", "狗"]);
+        let b = extract(&["我", "猫", "鱼", "鸟"]);
+        let known = HashSet::from(["我".to_string()]);
+        let comparison = compare_books(&a, &b, &known);
+        assert_eq!(comparison.shared_unknown.len(), 1);
+        assert_eq!(comparison.shared_unknown[0].word, "猫");
+        assert_eq!(comparison.shared_unknown[0].frequency_a, 2);
+        assert_eq!(comparison.unique_unknown_a, 1);
+        assert_eq!(comparison.unique_unknown_b, 2);
+        assert!((comparison.coverage_b - 0.25).abs() < 1e-9);
+        assert!((comparison.coverage_b_after_a - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chapter_comprehension_and_new_words() {
+        let chapter = |title: &str, words: &[&str]| ChapterSegmentation {
+            title: title.to_string(),
+            cut: words.iter().map(|w| w.to_string()).collect(),
+            sentences: vec![],
+        };
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![
+                chapter("一", &["我", "我", "猫", "狗"]),
+                chapter("二", &["我", "猫", "鱼", "鱼"]),
+            ],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let known = HashSet::from(["我".to_string()]);
+        let stats = get_chapter_stats(&extraction_res, &known);
+        assert!((stats[0].comprehension() - 0.5).abs() < 1e-9);
+        assert_eq!(stats[0].new_unknown_words, 2);
+        assert!((stats[1].comprehension() - 0.25).abs() < 1e-9);
+        assert_eq!(stats[1].new_unknown_words, 1);
+    }
+}
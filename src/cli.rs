@@ -1,13 +1,40 @@
-use std::{collections::HashSet, fs};
+use std::{collections::HashSet, fs, path::Path, time::SystemTime};
 
-use anyhow::Result;
-use clap::{Arg, ArgMatches, Command};
+use anyhow::{anyhow, Result};
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
 use rusqlite::Connection;
 
+use crate::analysis::{get_analysis_info, get_filtered_extraction_items, AnalysisQuery};
+use crate::config::get_config;
+use crate::db::books::db_books_select_by_title;
+use crate::db::cache::{db_cache_insert, db_cache_select, db_vocab_version};
+use crate::db::chars::{
+    db_char_overrides_del, db_char_overrides_select, db_char_overrides_select_all,
+    db_char_overrides_set,
+};
+use crate::db::history::db_history_select_all;
+use crate::db::ignore::{
+    db_ignored_add, db_ignored_del, db_ignored_select_all, db_ignored_select_words,
+};
+use crate::db::levels::db_levels_select_all;
+use crate::db::levels::{
+    db_levels_activate, db_levels_insert, db_levels_select_sources, db_ranks_update,
+};
 use crate::db::vocab::{
-    db_words_external_add, db_words_external_del, db_words_select_all, db_words_select_known,
-    VocabStatus,
+    db_words_external_add, db_words_external_del, db_words_external_del_source,
+    db_words_external_select_all, db_words_select_detailed, db_words_select_known,
+    parse_known_levels, ExternalSource, VocabStatus,
 };
+use crate::ebook::{open_as_flat_book, open_text_as_flat_book};
+use crate::extraction::{extract_vocab_from_segmented, ExtractionItem};
+use crate::filter::FilterExpr;
+use crate::history::{format_date, history_csv, record_vocab_snapshot};
+use crate::import::{parse_vocab_file, VocabFormat};
+use crate::levels::{annotate_levels, parse_frequency_list, parse_level_list, MAX_LEVEL};
+use crate::report::{build_report, default_report_queries, render_report, ReportFormat};
+use crate::segmentation::segment_book;
+use crate::vocab_export::{build_vocab_export, render_vocab_export, VocabExportFormat};
+use crate::vocabulary::{get_known_words_and_chars, split_chars, VocabularyContext};
 
 pub fn get_arg_matches() -> ArgMatches {
     Command::new("中文 vocab")
@@ -19,6 +46,24 @@ pub fn get_arg_matches() -> ArgMatches {
                     Arg::new("filename")
                         .required(true)
                         .help("path to file with one word per line"),
+                )
+                .arg(
+                    Arg::new("note")
+                        .long("note")
+                        .help("note to keep with the added words"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(value_parser!(VocabFormat))
+                        .default_value("plain")
+                        .help("plain (one word per line), pleco, skritter or hsk"),
+                )
+                .arg(
+                    Arg::new("apply")
+                        .long("apply")
+                        .action(ArgAction::SetTrue)
+                        .help("add the words, otherwise only the words that would be added are printed"),
                 ),
         )
         .subcommand(
@@ -31,26 +76,229 @@ pub fn get_arg_matches() -> ArgMatches {
                 ),
         )
         .subcommand(Command::new("show").about("Prints known words"))
+        .subcommand(
+            Command::new("ignore")
+                .about("Adds words from file to the ignore list, leaving them out of word lists")
+                .arg(
+                    Arg::new("filename")
+                        .required(true)
+                        .help("path to file with one word per line"),
+                ),
+        )
+        .subcommand(
+            Command::new("unignore")
+                .about("Removes words from file from the ignore list")
+                .arg(
+                    Arg::new("filename")
+                        .required(true)
+                        .help("path to file with one word per line"),
+                ),
+        )
+        .subcommand(
+            Command::new("list-ignored").about("Lists ignored words with when and where from they were added"),
+        )
+        .subcommand(
+            Command::new("mark-chars")
+                .about("Marks characters as known, regardless of the known words containing them")
+                .arg(
+                    Arg::new("characters")
+                        .required(true)
+                        .help("characters to mark, e.g 的地得"),
+                )
+                .arg(
+                    Arg::new("unknown")
+                        .long("unknown")
+                        .action(ArgAction::SetTrue)
+                        .help("mark the characters as unknown instead"),
+                ),
+        )
+        .subcommand(
+            Command::new("unmark-chars")
+                .about("Removes the known/unknown marks of characters")
+                .arg(
+                    Arg::new("characters")
+                        .required(true)
+                        .help("characters to unmark"),
+                ),
+        )
+        .subcommand(Command::new("list-chars").about("Lists characters marked as known or unknown"))
+        .subcommand(
+            Command::new("list-external")
+                .about("Lists sources of added vocabulary, or the words of one source")
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .value_parser(value_parser!(ExternalSource))
+                        .help("e.g file:words.txt, manual, word-list:<name> or unknown"),
+                ),
+        )
+        .subcommand(
+            Command::new("export-vocab")
+                .about("Prints all words with status, source and dates")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(value_parser!(VocabExportFormat))
+                        .default_value("csv")
+                        .help("csv, tsv or json"),
+                )
+                .arg(
+                    Arg::new("status")
+                        .long("status")
+                        .value_parser(parse_known_levels)
+                        .help(
+                            "only export words with one of these statuses, e.g young,mature,added-external",
+                        ),
+                )
+                .arg(
+                    Arg::new("chars")
+                        .long("chars")
+                        .action(ArgAction::SetTrue)
+                        .help("also export the characters of known words"),
+                ),
+        )
+        .subcommand(
+            Command::new("export-history")
+                .about("Prints history of known word and character counts as CSV"),
+        )
+        .subcommand(
+            Command::new("undo-external")
+                .about("Deletes all vocabulary added from a source")
+                .arg(
+                    Arg::new("source")
+                        .required(true)
+                        .value_parser(value_parser!(ExternalSource))
+                        .help("e.g file:words.txt, as shown by list-external"),
+                ),
+        )
+        .subcommand(
+            Command::new("import-levels")
+                .about("Imports word levels (e.g HSK) from file")
+                .arg(Arg::new("filename").required(true).help(
+                    "path to file with one word and its level per line, \
+                     or one word per line if --level is given",
+                ))
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .required(true)
+                        .help(
+                            "name of the level list (e.g hsk3), replaces previous import \
+                             of this source and makes it the active one",
+                        ),
+                )
+                .arg(
+                    Arg::new("level")
+                        .long("level")
+                        .value_parser(value_parser!(u8).range(1..=MAX_LEVEL as i64))
+                        .help("level of all words in file, adds to previous import of source"),
+                ),
+        )
+        .subcommand(
+            Command::new("list-levels").about("Lists imported level sources, marking the active one"),
+        )
+        .subcommand(
+            Command::new("use-levels")
+                .about("Chooses which imported level source is used for levels")
+                .arg(
+                    Arg::new("source")
+                        .required(true)
+                        .help("name of an imported level list (e.g hsk2)"),
+                ),
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Analyzes vocabulary of an epub/text file or a stored book")
+                .arg(Arg::new("path").help("path to epub or plain text file"))
+                .arg(
+                    Arg::new("book")
+                        .long("book")
+                        .help("title of a stored book"),
+                )
+                .arg(
+                    Arg::new("author")
+                        .long("author")
+                        .requires("book")
+                        .help("author of the stored book, if several share the title"),
+                )
+                .group(
+                    ArgGroup::new("input")
+                        .args(["path", "book"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("min-words")
+                        .long("min-words")
+                        .value_parser(value_parser!(u64))
+                        .default_value("1")
+                        .help("only include words occurring at least this often"),
+                )
+                .arg(
+                    Arg::new("min-chars")
+                        .long("min-chars")
+                        .value_parser(value_parser!(u64))
+                        .help("also include words with unknown characters occurring at least this often"),
+                )
+                .arg(
+                    Arg::new("max-level")
+                        .long("max-level")
+                        .value_parser(value_parser!(u8).range(1..=MAX_LEVEL as i64))
+                        .help("only include unknown words up to this level (e.g HSK)"),
+                )
+                .arg(
+                    Arg::new("exclude-guessable")
+                        .long("exclude-guessable")
+                        .action(ArgAction::SetTrue)
+                        .help("leave out unknown words consisting of known characters"),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .long("filter")
+                        .help("filter expression, e.g \"freq <= 10 and len >= 2\""),
+                )
+                .arg(
+                    Arg::new("words")
+                        .long("words")
+                        .action(ArgAction::SetTrue)
+                        .help("also print the unknown words with their frequency"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(value_parser!(ReportFormat))
+                        .help("print full report as json, csv or markdown instead"),
+                )
+                .arg(
+                    Arg::new("known")
+                        .long("known")
+                        .value_parser(parse_known_levels)
+                        .help(
+                            "Anki levels counting as known, e.g young,mature \
+                             (of new, learning, young, mature, suspended), default from config",
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("import-ranks")
+                .about("Imports general word frequency ranks from file, replaces previous ranks")
+                .arg(Arg::new("filename").required(true).help(
+                    "path to frequency list (e.g SUBTLEX-CH) with one word \
+                     and optionally its count per line",
+                )),
+        )
         .get_matches()
 }
 
-pub fn perform_add_external(data_conn: &Connection, filename: &str) -> Result<()> {
+pub fn perform_add_external(
+    data_conn: &mut Connection,
+    filename: &str,
+    format: VocabFormat,
+    note: Option<&str>,
+    apply: bool,
+) -> Result<()> {
     let file_str = fs::read_to_string(filename)?;
-    let words_to_add: HashSet<String> = file_str
-        .split('\n')
-        .map(|line| String::from(line.trim()))
-        .filter(|trimmed| !trimmed.is_empty())
-        .collect();
-    let words_known: HashSet<String> = db_words_select_all(data_conn)?
-        .into_iter()
-        .filter_map(|(word, status)| {
-            if !matches!(status, VocabStatus::Inactive) {
-                Some(word)
-            } else {
-                None
-            }
-        })
-        .collect();
+    let words_to_add = parse_vocab_file(&file_str, format);
+    let words_known = db_words_select_known(data_conn, &get_config().anki_levels.known)?;
     let words_unknown: &HashSet<&str> = &words_to_add
         .difference(&words_known)
         .map(|s| s.as_str())
@@ -58,10 +306,32 @@ pub fn perform_add_external(data_conn: &Connection, filename: &str) -> Result<()
     println!("amount saved: {}", &words_known.len());
     println!("amount to add: {}", &words_to_add.len());
     println!("amount new: {}", &words_unknown.len());
-    db_words_external_add(data_conn, words_unknown)
+    let file_name = Path::new(filename)
+        .file_name()
+        .map_or(filename.into(), |name| name.to_string_lossy());
+    let source = ExternalSource::File(file_name.to_string());
+    if !apply {
+        let mut new_words: Vec<&str> = words_unknown.iter().copied().collect();
+        new_words.sort();
+        println!("would add {} words as {}", new_words.len(), source);
+        for word in new_words {
+            println!("+ {}", word);
+        }
+        println!("nothing written, pass --apply to add them");
+        return Ok(());
+    }
+    let added = db_words_external_add(data_conn, words_unknown, &source, note)?;
+    println!("added {} words as {}", added.len(), source);
+    for word in &added {
+        println!("+ {}", word);
+    }
+    if !added.is_empty() {
+        record_vocab_snapshot(data_conn)?;
+    }
+    Ok(())
 }
 
-pub fn perform_delete_external(data_conn: &Connection, filename: &str) -> Result<()> {
+pub fn perform_delete_external(data_conn: &mut Connection, filename: &str) -> Result<()> {
     let file_str = fs::read_to_string(filename)?;
     let words_to_delete: HashSet<String> = file_str
         .split('\n')
@@ -69,13 +339,392 @@ pub fn perform_delete_external(data_conn: &Connection, filename: &str) -> Result
         .filter(|trimmed| !trimmed.is_empty())
         .collect();
     println!("amount to delete: {}", &words_to_delete.len());
-    db_words_external_del(data_conn, &words_to_delete)
+    let deleted = db_words_external_del(data_conn, &words_to_delete)?;
+    println!("deleted {} words", deleted.len());
+    for word in &deleted {
+        println!("- {}", word);
+    }
+    if !deleted.is_empty() {
+        record_vocab_snapshot(data_conn)?;
+    }
+    Ok(())
+}
+
+/// Without source print every source with its amount of words, otherwise the source's words
+pub fn perform_list_external(
+    data_conn: &Connection,
+    source: Option<&ExternalSource>,
+) -> Result<()> {
+    let words = db_words_external_select_all(data_conn)?;
+    match source {
+        Some(source) => {
+            for word in words.iter().filter(|word| &word.source == source) {
+                println!(
+                    "{}\t{}\t{}",
+                    word.word,
+                    word.added_at.map(format_date).unwrap_or_default(),
+                    word.note.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        None => {
+            // (amount of words, last time added) per source
+            let mut sources: Vec<(&ExternalSource, usize, Option<SystemTime>)> = vec![];
+            for word in &words {
+                match sources.iter_mut().find(|(s, _, _)| *s == &word.source) {
+                    Some((_, amount, last_added)) => {
+                        *amount += 1;
+                        *last_added = (*last_added).max(word.added_at);
+                    }
+                    None => sources.push((&word.source, 1, word.added_at)),
+                }
+            }
+            for (source, amount, last_added) in sources {
+                println!(
+                    "{}\t{} words\t{}",
+                    source,
+                    amount,
+                    last_added.map(format_date).unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn perform_undo_external(data_conn: &mut Connection, source: &ExternalSource) -> Result<()> {
+    let deleted = db_words_external_del_source(data_conn, source)?;
+    if deleted.is_empty() {
+        return Err(anyhow!("no words added from {}", source));
+    }
+    println!("deleted {} words added from {}", deleted.len(), source);
+    for word in &deleted {
+        println!("- {}", word);
+    }
+    record_vocab_snapshot(data_conn)?;
+    Ok(())
+}
+
+pub fn perform_ignore(data_conn: &mut Connection, filename: &str) -> Result<()> {
+    let file_str = fs::read_to_string(filename)?;
+    let words: HashSet<&str> = file_str
+        .split('\n')
+        .map(|line| line.trim())
+        .filter(|trimmed| !trimmed.is_empty())
+        .collect();
+    let file_name = Path::new(filename)
+        .file_name()
+        .map_or(filename.into(), |name| name.to_string_lossy());
+    let source = ExternalSource::File(file_name.to_string());
+    let exclude_from_counts = get_config().ignore.exclude_from_counts;
+    let added = db_ignored_add(data_conn, &words, &source, exclude_from_counts)?;
+    println!("ignoring {} new words", added.len());
+    for word in &added {
+        println!("+ {}", word);
+    }
+    Ok(())
+}
+
+pub fn perform_unignore(data_conn: &mut Connection, filename: &str) -> Result<()> {
+    let file_str = fs::read_to_string(filename)?;
+    let words: HashSet<String> = file_str
+        .split('\n')
+        .map(|line| String::from(line.trim()))
+        .filter(|trimmed| !trimmed.is_empty())
+        .collect();
+    let deleted = db_ignored_del(data_conn, &words, get_config().ignore.exclude_from_counts)?;
+    println!("no longer ignoring {} words", deleted.len());
+    for word in &deleted {
+        println!("- {}", word);
+    }
+    Ok(())
+}
+
+pub fn perform_mark_chars(data_conn: &mut Connection, characters: &str, known: bool) -> Result<()> {
+    let chars = split_chars(characters);
+    let changed = db_char_overrides_set(data_conn, &chars, known)?;
+    if !changed.is_empty() {
+        record_vocab_snapshot(data_conn)?;
+    }
+    let status = if known { "known" } else { "unknown" };
+    println!("marked {} characters as {}", changed.len(), status);
+    for hanzi in &changed {
+        println!("{} {}", if known { "+" } else { "-" }, hanzi);
+    }
+    Ok(())
+}
+
+pub fn perform_unmark_chars(data_conn: &mut Connection, characters: &str) -> Result<()> {
+    let deleted = db_char_overrides_del(data_conn, &split_chars(characters))?;
+    if !deleted.is_empty() {
+        record_vocab_snapshot(data_conn)?;
+    }
+    println!("unmarked {} characters", deleted.len());
+    for hanzi in &deleted {
+        println!("= {}", hanzi);
+    }
+    Ok(())
+}
+
+pub fn perform_list_chars(data_conn: &Connection) -> Result<()> {
+    for char_override in db_char_overrides_select_all(data_conn)? {
+        println!(
+            "{}\t{}\t{}",
+            char_override.hanzi,
+            if char_override.known {
+                "known"
+            } else {
+                "unknown"
+            },
+            format_date(char_override.added_at)
+        );
+    }
+    Ok(())
+}
+
+pub fn perform_list_ignored(data_conn: &Connection) -> Result<()> {
+    for word in db_ignored_select_all(data_conn)? {
+        println!(
+            "{}\t{}\t{}",
+            word.word,
+            format_date(word.added_at),
+            word.source
+        );
+    }
+    Ok(())
+}
+
+pub fn perform_export_vocab(
+    data_conn: &Connection,
+    format: VocabExportFormat,
+    statuses: Option<&[VocabStatus]>,
+    include_chars: bool,
+) -> Result<()> {
+    let known_levels = get_config().anki_levels.known;
+    let words = db_words_select_detailed(data_conn, &known_levels)?;
+    let char_overrides = db_char_overrides_select(data_conn)?;
+    let entries = build_vocab_export(
+        &words,
+        statuses,
+        include_chars.then_some((known_levels.as_slice(), &char_overrides)),
+    );
+    print!("{}", render_vocab_export(&entries, format)?);
+    Ok(())
+}
+
+pub fn perform_export_history(data_conn: &Connection) -> Result<()> {
+    print!("{}", history_csv(&db_history_select_all(data_conn)?));
+    Ok(())
 }
 
 pub fn show(conn: &Connection) -> Result<()> {
-    let known_words = db_words_select_known(conn)?;
+    let known_words = db_words_select_known(conn, &get_config().anki_levels.known)?;
     for item in known_words {
         println!("{}", item);
     }
     Ok(())
 }
+
+pub fn perform_import_levels(
+    data_conn: &mut Connection,
+    filename: &str,
+    source: &str,
+    level: Option<u8>,
+) -> Result<()> {
+    let file_str = fs::read_to_string(filename)?;
+    let levels = parse_level_list(&file_str, level)?;
+    println!("amount to import: {}", levels.len());
+    // a file with a given level is one of several files of the source
+    db_levels_insert(data_conn, &levels, source, level.is_none())
+}
+
+pub fn perform_list_levels(data_conn: &Connection) -> Result<()> {
+    for (source, active, amount) in db_levels_select_sources(data_conn)? {
+        let marker = if active { "*" } else { " " };
+        println!("{} {}\t{} words", marker, source, amount);
+    }
+    Ok(())
+}
+
+pub fn perform_use_levels(data_conn: &mut Connection, source: &str) -> Result<()> {
+    if !db_levels_activate(data_conn, source)? {
+        return Err(anyhow!("no imported level source named {}", source));
+    }
+    println!("using levels of {}", source);
+    Ok(())
+}
+
+pub fn perform_import_ranks(data_conn: &mut Connection, filename: &str) -> Result<()> {
+    let file_str = fs::read_to_string(filename)?;
+    let ranks = parse_frequency_list(&file_str);
+    println!("amount to import: {}", ranks.len());
+    db_ranks_update(data_conn, &ranks)
+}
+
+/// Book to analyze, either a file to segment or the title of a stored book
+pub enum AnalyzeInput {
+    Path(String),
+    Stored {
+        title: String,
+        author: Option<String>,
+    },
+}
+
+pub fn perform_analyze(
+    data_conn: &Connection,
+    input: AnalyzeInput,
+    query: &AnalysisQuery,
+    known_levels: &[VocabStatus],
+    print_words: bool,
+    format: Option<ReportFormat>,
+) -> Result<()> {
+    let (title, author, book) = match input {
+        AnalyzeInput::Path(path) => {
+            let flat_book = if path.to_lowercase().ends_with(".epub") {
+                open_as_flat_book(&path)?
+            } else {
+                open_text_as_flat_book(&path)?
+            };
+            let book = segment_book(&flat_book);
+            (flat_book.title, flat_book.author, book)
+        }
+        AnalyzeInput::Stored { title, author } => {
+            let (author, book) = db_books_select_by_title(data_conn, &title, author.as_deref())?
+                .ok_or_else(|| anyhow!("no stored book titled {}", title))?;
+            (title, author, book)
+        }
+    };
+    let mut extraction_res = extract_vocab_from_segmented(book);
+    annotate_levels(&mut extraction_res, &db_levels_select_all(data_conn)?);
+    let char_overrides = db_char_overrides_select(data_conn)?;
+    let vocabulary = VocabularyContext {
+        known_words_and_chars: get_known_words_and_chars(
+            db_words_select_known(data_conn, known_levels)?,
+            &char_overrides,
+        ),
+        char_overrides,
+        ignored_words: db_ignored_select_words(data_conn)?,
+        exclude_ignored: get_config().ignore.exclude_from_counts,
+    };
+    let (known, char_overrides) = (
+        &vocabulary.known_words_and_chars,
+        &vocabulary.char_overrides,
+    );
+    let ignored_for_counts = vocabulary.ignored_for_counts();
+
+    if let Some(format) = format {
+        let report = build_report(
+            &title,
+            &author,
+            &extraction_res,
+            &vocabulary,
+            &default_report_queries(),
+            query,
+        );
+        print!("{}", render_report(&report, format)?);
+        return Ok(());
+    }
+
+    let book_hash = extraction_res.segmented_book.content_hash();
+    let vocab_version = db_vocab_version(data_conn)?;
+    let cache_key = query.cache_key(known_levels, ignored_for_counts.is_some());
+    let info = match db_cache_select(data_conn, &book_hash, &cache_key, vocab_version)? {
+        Some(info) => info,
+        None => {
+            let info = get_analysis_info(
+                &extraction_res,
+                query,
+                known,
+                ignored_for_counts,
+                char_overrides,
+            );
+            db_cache_insert(data_conn, &book_hash, &cache_key, vocab_version, &info)?;
+            info
+        }
+    };
+    if author.is_empty() {
+        println!("{} ({})", title, query);
+    } else {
+        println!("{} by {} ({})", title, author, query);
+    }
+    println!("{:<14}{:>10}{:>10}", "", "all", "unknown");
+    println!(
+        "{:<14}{:>10}{:>10}",
+        "total words", info.total_words, info.unknown_total_words
+    );
+    println!(
+        "{:<14}{:>10}{:>10}",
+        "unique words", info.unique_words, info.unknown_unique_words
+    );
+    println!(
+        "{:<14}{:>10}{:>10}",
+        "total chars", info.total_chars, info.unknown_total_chars
+    );
+    println!(
+        "{:<14}{:>10}{:>10}",
+        "unique chars", info.unique_chars, info.unknown_unique_chars
+    );
+    println!(
+        "unknown words: {} with only known characters, {} with unknown characters",
+        info.guessable_unique_words,
+        info.new_char_unique_words()
+    );
+    if print_words {
+        let mut unknown: Vec<&ExtractionItem> =
+            get_filtered_extraction_items(&extraction_res, query, known, char_overrides)
+                .into_iter()
+                .filter(|item| {
+                    !known.contains(&item.word) && !vocabulary.ignored_words.contains(&item.word)
+                })
+                .collect();
+        unknown.sort_by(|a, b| b.frequency.cmp(&a.frequency).then(a.word.cmp(&b.word)));
+        println!();
+        for item in unknown {
+            println!("{}\t{}", item.word, item.frequency);
+        }
+    }
+    Ok(())
+}
+
+/// Build analysis query from the flags of the analyze command
+pub fn analyze_query_from_matches(matches: &ArgMatches) -> Result<AnalysisQuery> {
+    let mut query = AnalysisQuery::new(*matches.get_one::<u64>("min-words").unwrap());
+    query.min_occurrence_unknown_chars = matches.get_one::<u64>("min-chars").copied();
+    query.max_level = matches.get_one::<u8>("max-level").copied();
+    query.exclude_guessable = matches.get_flag("exclude-guessable");
+    query.filter = matches
+        .get_one::<String>("filter")
+        .map(|filter| FilterExpr::parse(filter))
+        .transpose()?;
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::cli::perform_add_external;
+    use crate::config::init_test_config;
+    use crate::db::{open_test_db, vocab::db_words_external_select_all};
+    use crate::import::VocabFormat;
+
+    #[test]
+    fn add_external_only_with_apply() {
+        init_test_config();
+        let mut conn = open_test_db();
+        let path = env::temp_dir().join(format!("han-cihui-add-{}.txt", std::process::id()));
+        fs::write(&path, "猫\n狗\n").unwrap();
+        let filename = path.to_str().unwrap();
+
+        perform_add_external(&mut conn, filename, VocabFormat::Plain, None, false).unwrap();
+        assert!(db_words_external_select_all(&conn).unwrap().is_empty());
+        let snapshots: i64 = conn
+            .query_row("SELECT count(*) FROM vocab_history", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(snapshots, 0);
+
+        perform_add_external(&mut conn, filename, VocabFormat::Plain, None, true).unwrap();
+        assert_eq!(db_words_external_select_all(&conn).unwrap().len(), 2);
+        fs::remove_file(path).unwrap();
+    }
+}
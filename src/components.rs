@@ -0,0 +1,351 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::OnceLock,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{analysis::UnknownCharInfo, config::get_config};
+
+/// Bundled IDS data, covers only a hand-picked set of ~800 common characters
+pub const IDS_TEXT: &str = include_str!("../ids.txt");
+
+/// Amount of known characters listed per shared component
+const EXAMPLES_PER_COMPONENT: usize = 3;
+
+// components that usually carry the meaning of a character, the other part is taken as phonetic
+const SEMANTIC_COMPONENTS: &str = "氵冫亻扌口木讠纟艹忄心日目⻊足钅饣门阝辶走户宀穴⺮雨女火灬月土石禾衤疒贝马车鸟虫鱼刂力犭王攵彳欠广厂米牛立页山囗皿礻巾手身田";
+
+/// Characters mapped to their ideographic description sequence
+pub type Decompositions = HashMap<String, String>;
+
+/// Parse IDS data in cjkvi-ids format, taking the first sequence of each character
+pub fn parse_ids(text: &str) -> Decompositions {
+    text.lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with(';'))
+        .filter_map(|line| {
+            let mut fields = line.split('\t').skip(1);
+            let hanzi = fields.next()?.trim();
+            // strip source tags like [GTJ]
+            let ids: String = fields
+                .next()?
+                .split('[')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            if hanzi.is_empty() || ids.is_empty() || ids == hanzi {
+                return None;
+            }
+            Some((hanzi.to_string(), ids))
+        })
+        .collect()
+}
+
+/// Decompositions of the given IDS file, or the bundled ones
+pub fn load_decompositions(ids_file: Option<&Path>) -> Result<Decompositions> {
+    match ids_file {
+        Some(path) => {
+            let text = fs::read_to_string(path)
+                .with_context(|| format!("could not read IDS file {}", path.display()))?;
+            Ok(parse_ids(&text))
+        }
+        None => Ok(parse_ids(IDS_TEXT)),
+    }
+}
+
+/// Bundled decompositions
+pub fn get_bundled_decompositions() -> &'static Decompositions {
+    static BUNDLED: OnceLock<Decompositions> = OnceLock::new();
+    BUNDLED.get_or_init(|| parse_ids(IDS_TEXT))
+}
+
+/// Decompositions of the IDS file set in the config, or the bundled ones
+pub fn get_decompositions() -> Result<&'static Decompositions> {
+    static DECOMPOSITIONS: OnceLock<Decompositions> = OnceLock::new();
+    match get_config().ids_file {
+        Some(path) => {
+            if let Some(decompositions) = DECOMPOSITIONS.get() {
+                return Ok(decompositions);
+            }
+            let decompositions = load_decompositions(Some(&path))?;
+            Ok(DECOMPOSITIONS.get_or_init(|| decompositions))
+        }
+        None => Ok(get_bundled_decompositions()),
+    }
+}
+
+fn is_idc(c: char) -> bool {
+    ('\u{2FF0}'..='\u{2FFB}').contains(&c)
+}
+
+fn idc_arity(c: char) -> usize {
+    match c {
+        '\u{2FF2}' | '\u{2FF3}' => 3,
+        _ => 2,
+    }
+}
+
+// description characters and components (single characters or &entity; references)
+fn tokenize_ids(ids: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut rest = ids;
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            '&' => rest.find(';').map_or(rest.len(), |end| end + 1),
+            _ => c.len_utf8(),
+        };
+        tokens.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+// end index of the operand starting at tokens[start]
+fn operand_end(tokens: &[&str], start: usize) -> usize {
+    match tokens.get(start).and_then(|token| token.chars().next()) {
+        Some(c) if is_idc(c) => {
+            let mut end = start + 1;
+            for _ in 0..idc_arity(c) {
+                end = operand_end(tokens, end);
+            }
+            end
+        }
+        Some(_) => start + 1,
+        None => start,
+    }
+}
+
+/// All components occurring in the sequence, in order
+pub fn ids_components(ids: &str) -> Vec<&str> {
+    tokenize_ids(ids)
+        .into_iter()
+        .filter(|token| !token.chars().next().is_some_and(is_idc))
+        .collect()
+}
+
+/// Guess the phonetic component: the part next to a common semantic component,
+/// or the right/bottom/inner part otherwise
+pub fn phonetic_component(ids: &str) -> Option<&str> {
+    let tokens = tokenize_ids(ids);
+    let idc = tokens.first()?.chars().next()?;
+    if !is_idc(idc) || idc_arity(idc) != 2 {
+        return None;
+    }
+    let first_end = operand_end(&tokens, 1);
+    let (first, second) = (&tokens[1..first_end], &tokens[first_end..]);
+    let is_semantic = |part: &[&str]| part.len() == 1 && SEMANTIC_COMPONENTS.contains(part[0]);
+    let phonetic = if is_semantic(first) || !is_semantic(second) {
+        second
+    } else {
+        first
+    };
+    match phonetic {
+        [component] => Some(component),
+        _ => None,
+    }
+}
+
+/// An unknown character with the components it shares with known characters
+pub struct ComponentCharInfo {
+    pub hanzi: String,
+    pub frequency: u64,
+    pub ids: Option<String>,
+    /// known components with some known characters containing them
+    /// (empty if the component is only known as a character itself)
+    pub known_components: Vec<(String, Vec<String>)>,
+    pub phonetic: Option<String>,
+}
+
+/// Unknown characters of a phonetic series, together with the known ones
+pub struct PhoneticGroup {
+    pub phonetic: String,
+    pub unknown: Vec<String>,
+    pub known: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ComponentAnalysis {
+    /// unknown characters, most known components first, then by frequency
+    pub chars: Vec<ComponentCharInfo>,
+    /// series with at least two characters, most unknown characters first
+    pub phonetic_groups: Vec<PhoneticGroup>,
+}
+
+/// Analyze the components of unknown characters with respect to the known characters
+pub fn get_component_analysis(
+    unknown_chars: &[UnknownCharInfo],
+    known_chars: &HashSet<String>,
+    decompositions: &Decompositions,
+) -> ComponentAnalysis {
+    // component => known characters containing it
+    let mut known_by_component: HashMap<&str, Vec<&str>> = HashMap::new();
+    // phonetic => known characters of its series
+    let mut known_by_phonetic: HashMap<&str, Vec<&str>> = HashMap::new();
+    for hanzi in known_chars {
+        if let Some(ids) = decompositions.get(hanzi) {
+            let mut components = ids_components(ids);
+            components.sort();
+            components.dedup();
+            for component in components {
+                known_by_component.entry(component).or_default().push(hanzi);
+            }
+            if let Some(phonetic) = phonetic_component(ids) {
+                known_by_phonetic.entry(phonetic).or_default().push(hanzi);
+            }
+        }
+    }
+    for known in known_by_component
+        .values_mut()
+        .chain(known_by_phonetic.values_mut())
+    {
+        known.sort();
+    }
+
+    let mut chars: Vec<ComponentCharInfo> = unknown_chars
+        .iter()
+        .map(|info| {
+            let ids = decompositions.get(&info.hanzi);
+            let mut components = ids.map(|ids| ids_components(ids)).unwrap_or_default();
+            let mut seen = HashSet::new();
+            components.retain(|component| seen.insert(*component));
+            let known_components = components
+                .into_iter()
+                .filter_map(|component| {
+                    let containing = known_by_component.get(component);
+                    if containing.is_none() && !known_chars.contains(component) {
+                        return None;
+                    }
+                    let examples = containing
+                        .map(|known| {
+                            known
+                                .iter()
+                                .take(EXAMPLES_PER_COMPONENT)
+                                .map(|hanzi| hanzi.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    Some((component.to_string(), examples))
+                })
+                .collect();
+            ComponentCharInfo {
+                hanzi: info.hanzi.clone(),
+                frequency: info.frequency,
+                ids: ids.cloned(),
+                known_components,
+                phonetic: ids
+                    .and_then(|ids| phonetic_component(ids))
+                    .map(|phonetic| phonetic.to_string()),
+            }
+        })
+        .collect();
+    chars.sort_by(|a, b| {
+        b.known_components
+            .len()
+            .cmp(&a.known_components.len())
+            .then(b.frequency.cmp(&a.frequency))
+            .then(a.hanzi.cmp(&b.hanzi))
+    });
+
+    let mut unknown_by_phonetic: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for info in &chars {
+        if let Some(phonetic) = &info.phonetic {
+            unknown_by_phonetic
+                .entry(phonetic)
+                .or_default()
+                .push(info.hanzi.clone());
+        }
+    }
+    let mut phonetic_groups: Vec<PhoneticGroup> = unknown_by_phonetic
+        .into_iter()
+        .map(|(phonetic, unknown)| PhoneticGroup {
+            phonetic: phonetic.to_string(),
+            unknown,
+            known: known_by_phonetic
+                .get(phonetic)
+                .map(|known| known.iter().map(|hanzi| hanzi.to_string()).collect())
+                .unwrap_or_default(),
+        })
+        .filter(|group| group.unknown.len() + group.known.len() >= 2)
+        .collect();
+    // stable sort keeps the groups ordered by phonetic within ties
+    phonetic_groups.sort_by(|a, b| {
+        b.unknown
+            .len()
+            .cmp(&a.unknown.len())
+            .then(b.known.len().cmp(&a.known.len()))
+    });
+
+    ComponentAnalysis {
+        chars,
+        phonetic_groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::analysis::UnknownCharInfo;
+    use crate::components::{
+        get_component_analysis, ids_components, load_decompositions, parse_ids, phonetic_component,
+        IDS_TEXT,
+    };
+
+    #[test]
+    fn parse_and_split_sequences() {
+        let decompositions = parse_ids(
+            "# comment\nU+6E05\t清\t⿰氵青\nU+9752\t青\t青\nU+90FD\t都\t⿰者阝[GTJ]\t⿰者⻏",
+        );
+        assert_eq!(decompositions.len(), 2);
+        assert_eq!(decompositions["都"], "⿰者阝");
+        assert_eq!(ids_components("⿰氵⿱艹两"), vec!["氵", "艹", "两"]);
+        assert_eq!(phonetic_component("⿰氵青"), Some("青"));
+        assert_eq!(phonetic_component("⿰者阝"), Some("者"));
+        assert_eq!(phonetic_component("⿱艹化"), Some("化"));
+        assert_eq!(phonetic_component("⿰氵⿱艹两"), None);
+        assert_eq!(phonetic_component("⿲彳圭亍"), None);
+        assert!(parse_ids(IDS_TEXT).len() > 500);
+    }
+
+    #[test]
+    fn fail_on_unreadable_ids_file() {
+        let path = std::env::temp_dir().join("han_cihui_missing_ids.txt");
+        let err = load_decompositions(Some(&path)).unwrap_err();
+        assert!(err.to_string().contains("could not read IDS file"));
+        assert_eq!(
+            load_decompositions(None).unwrap().len(),
+            parse_ids(IDS_TEXT).len()
+        );
+    }
+
+    #[test]
+    fn rank_and_group_unknown_chars() {
+        let decompositions = parse_ids(IDS_TEXT);
+        let unknown = |hanzi: &str, frequency: u64| UnknownCharInfo {
+            hanzi: hanzi.to_string(),
+            frequency,
+            words: vec![],
+        };
+        let unknown_chars = vec![unknown("晴", 5), unknown("睛", 3), unknown("猜", 8)];
+        let known_chars: HashSet<String> =
+            ["清", "日", "请"].iter().map(|c| c.to_string()).collect();
+        let analysis = get_component_analysis(&unknown_chars, &known_chars, &decompositions);
+        let ranked: Vec<&str> = analysis.chars.iter().map(|c| c.hanzi.as_str()).collect();
+        assert_eq!(ranked, vec!["晴", "猜", "睛"]);
+        assert_eq!(
+            analysis.chars[0].known_components,
+            vec![
+                ("日".to_string(), vec![]),
+                ("青".to_string(), vec!["清".to_string(), "请".to_string()])
+            ]
+        );
+        assert_eq!(analysis.phonetic_groups.len(), 1);
+        let group = &analysis.phonetic_groups[0];
+        assert_eq!(group.phonetic, "青");
+        assert_eq!(group.unknown, vec!["晴", "猜", "睛"]);
+        assert_eq!(group.known, vec!["清", "请"]);
+    }
+}
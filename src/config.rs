@@ -1,4 +1,4 @@
-use home::home_dir;
+use std::collections::BTreeMap;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
@@ -6,6 +6,8 @@ use std::{env, fs};
 
 use serde::{Deserialize, Serialize};
 
+use crate::db::vocab::VocabStatus;
+
 pub fn init_config(data_dir: &Path) {
     let config_path = data_dir.join("config.toml");
     let config = if !config_path.exists() {
@@ -26,6 +28,11 @@ pub fn init_config(data_dir: &Path) {
             anki_db_path,
             anki_notes,
             export_base_path,
+            filter_presets: BTreeMap::new(),
+            comprehension_threshold: default_comprehension_threshold(),
+            anki_levels: AnkiLevelsConfig::default(),
+            ignore: IgnoreConfig::default(),
+            ids_file: None,
         };
         fs::write(
             config_path,
@@ -46,6 +53,21 @@ pub fn get_config() -> Config {
 
 static CONFIG: OnceLock<Config> = OnceLock::new();
 
+/// Set a default config without Anki collection, for tests of code reading the config
+#[cfg(test)]
+pub(crate) fn init_test_config() {
+    CONFIG.get_or_init(|| Config {
+        anki_db_path: PathBuf::new(),
+        anki_notes: vec![],
+        export_base_path: env::temp_dir(),
+        filter_presets: BTreeMap::new(),
+        comprehension_threshold: default_comprehension_threshold(),
+        anki_levels: AnkiLevelsConfig::default(),
+        ignore: IgnoreConfig::default(),
+        ids_file: None,
+    });
+}
+
 #[cfg(not(debug_assertions))]
 pub const TAGGER_BIN: &str = "han-shaixuan";
 #[cfg(debug_assertions)]
@@ -56,6 +78,62 @@ pub struct Config {
     pub anki_db_path: PathBuf,
     pub anki_notes: Vec<String>,
     pub export_base_path: PathBuf,
+    /// Named filter expressions, applied in the analysis tab as @name
+    #[serde(default)]
+    pub filter_presets: BTreeMap<String, String>,
+    /// Chapters with a lower share of known words (in percent) are highlighted
+    #[serde(default = "default_comprehension_threshold")]
+    pub comprehension_threshold: u64,
+    #[serde(default)]
+    pub anki_levels: AnkiLevelsConfig,
+    #[serde(default)]
+    pub ignore: IgnoreConfig,
+    /// IDS file (cjkvi-ids format) to decompose characters with instead of the bundled one,
+    /// which only covers ~800 common characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ids_file: Option<PathBuf>,
+}
+
+fn default_comprehension_threshold() -> u64 {
+    90
+}
+
+/// How Anki cards map to levels and which levels count as known
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct AnkiLevelsConfig {
+    /// review cards with at least this interval (in days) are mature, otherwise young
+    pub mature_interval: i64,
+    /// cards that lapsed at least this often are at most young
+    pub leech_lapses: i64,
+    /// levels whose words count as known, unless an analysis chooses others
+    pub known: Vec<VocabStatus>,
+}
+
+// same as Anki's own notion of mature cards and leeches,
+// known levels are the cards that were studied at least once
+impl Default for AnkiLevelsConfig {
+    fn default() -> Self {
+        Self {
+            mature_interval: 21,
+            leech_lapses: 8,
+            known: vec![
+                VocabStatus::Learning,
+                VocabStatus::Young,
+                VocabStatus::Mature,
+            ],
+        }
+    }
+}
+
+/// How the global list of ignored words is used
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct IgnoreConfig {
+    /// add words tagged as ignore in a word list to the ignore list
+    pub from_tags: bool,
+    /// leave ignored words out of the unknown word counts of analyses
+    pub exclude_from_counts: bool,
 }
 
 // making sure that when developing the path to the data directory has to be explicitely set
@@ -68,7 +146,7 @@ pub fn get_data_dir() -> PathBuf {
 pub fn get_data_dir() -> PathBuf {
     match env::var("DATA_DIR") {
         Ok(path) => PathBuf::from(path),
-        Err(_) => home_dir().expect("could not determine current user's home directory"),
+        Err(_) => home::home_dir().expect("could not determine current user's home directory"),
     }
 }
 
@@ -0,0 +1,321 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::extraction::contains_hanzi;
+use crate::segmentation::BookSegmentation;
+
+/// A word's occurrences across all books of the library
+#[derive(Clone)]
+pub struct CorpusWord {
+    pub word: String,
+    pub frequency: u64,
+    pub books_containing: usize,
+    /// Juilland's D over books (0: concentrated in one book, 1: evenly spread)
+    pub dispersion: f64,
+}
+
+pub struct CorpusIndex {
+    pub book_amount: usize,
+    pub total_words: u64,
+    pub words: Vec<CorpusWord>,
+}
+
+impl CorpusIndex {
+    pub fn build(books: &[&BookSegmentation]) -> Self {
+        let book_sizes: Vec<u64> = books
+            .iter()
+            .map(|book| {
+                book.chapter_cuts
+                    .iter()
+                    .map(|chapter| chapter.cut.len() as u64)
+                    .sum()
+            })
+            .collect();
+        let mut book_frequencies: HashMap<&str, Vec<u64>> = HashMap::new();
+        for (i, book) in books.iter().enumerate() {
+            for word in book.chapter_cuts.iter().flat_map(|chapter| &chapter.cut) {
+                book_frequencies
+                    .entry(word.as_str())
+                    .or_insert_with(|| vec![0; books.len()])[i] += 1;
+            }
+        }
+        let words = book_frequencies
+            .into_iter()
+            .filter(|(word, _)| contains_hanzi(word))
+            .map(|(word, frequencies)| CorpusWord {
+                word: word.to_string(),
+                frequency: frequencies.iter().sum(),
+                books_containing: frequencies.iter().filter(|f| **f > 0).count(),
+                dispersion: juilland_d(&frequencies, &book_sizes),
+            })
+            .collect();
+        Self {
+            book_amount: books.len(),
+            total_words: book_sizes.iter().sum(),
+            words,
+        }
+    }
+
+    /// Unknown words that occur in at least min_books books,
+    /// ranked by the amount of books containing them, then by frequency
+    pub fn recommendations(
+        &self,
+        known_words: &HashSet<String>,
+        min_books: usize,
+    ) -> Vec<CorpusWord> {
+        let mut recommended: Vec<CorpusWord> = self
+            .words
+            .iter()
+            .filter(|w| w.books_containing >= min_books && !known_words.contains(&w.word))
+            .cloned()
+            .collect();
+        recommended.sort_by(|a, b| {
+            b.books_containing
+                .cmp(&a.books_containing)
+                .then(b.frequency.cmp(&a.frequency))
+                .then(a.word.cmp(&b.word))
+        });
+        recommended
+    }
+}
+
+/// Word frequencies of a single book
+pub struct BookVocabulary {
+    pub title: String,
+    pub author: String,
+    pub frequencies: HashMap<String, u64>,
+}
+
+impl BookVocabulary {
+    pub fn new(title: &str, author: &str, book: &BookSegmentation) -> Self {
+        let mut frequencies = HashMap::new();
+        for word in book.chapter_cuts.iter().flat_map(|chapter| &chapter.cut) {
+            if contains_hanzi(word) {
+                *frequencies.entry(word.clone()).or_insert(0) += 1;
+            }
+        }
+        Self {
+            title: title.to_string(),
+            author: author.to_string(),
+            frequencies,
+        }
+    }
+
+    /// Share of word occurrences that are known
+    pub fn coverage(&self, known_words: &HashSet<String>) -> f64 {
+        self.coverage_with(known_words, &HashSet::new())
+    }
+
+    // coverage when the extra words are known as well
+    fn coverage_with(&self, known_words: &HashSet<String>, extra: &HashSet<&String>) -> f64 {
+        let total: u64 = self.frequencies.values().sum();
+        if total == 0 {
+            return 1.0;
+        }
+        let known: u64 = self
+            .frequencies
+            .iter()
+            .filter(|(word, _)| known_words.contains(*word) || extra.contains(word))
+            .map(|(_, freq)| freq)
+            .sum();
+        known as f64 / total as f64
+    }
+
+    /// Most frequent unknown words that need to be learned to reach the target coverage (0.0 - 1.0)
+    pub fn words_to_target(
+        &self,
+        known_words: &HashSet<String>,
+        target_coverage: f64,
+    ) -> Vec<(String, u64)> {
+        let total: u64 = self.frequencies.values().sum();
+        let mut unknown: Vec<(&String, u64)> = self
+            .frequencies
+            .iter()
+            .filter(|(word, _)| !known_words.contains(*word))
+            .map(|(word, freq)| (word, *freq))
+            .collect();
+        unknown.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let mut covered = total - unknown.iter().map(|(_, freq)| freq).sum::<u64>();
+        let mut words = Vec::new();
+        for (word, freq) in unknown {
+            if covered as f64 >= target_coverage * total as f64 {
+                break;
+            }
+            covered += freq;
+            words.push((word.clone(), freq));
+        }
+        words
+    }
+}
+
+/// A book of the planned reading order
+pub struct ReadingStep {
+    pub title: String,
+    pub author: String,
+    /// projected share of known words when starting the book
+    pub coverage: f64,
+    /// unknown words occurring at least min_frequency times, learned while reading
+    pub words_learned: usize,
+    /// most frequent unknown words (with frequency) to learn before reading
+    /// to reach the target coverage, empty if the target is already met
+    pub words_to_target: Vec<(String, u64)>,
+}
+
+/// Plan the order in which to read books
+///
+/// repeatedly picks the book with the highest projected coverage and assumes that
+/// all of its unknown words occurring at least min_frequency times are learned while reading it,
+/// books below the target coverage (0.0 - 1.0) are therefore postponed as long as possible,
+/// the words needed to lift such a book to the target are assumed to be learned before reading it
+pub fn plan_reading_order(
+    books: &[&BookVocabulary],
+    known_words: &HashSet<String>,
+    min_frequency: u64,
+    target_coverage: f64,
+) -> Vec<ReadingStep> {
+    let mut known = known_words.clone();
+    let mut remaining: Vec<&BookVocabulary> = books.to_vec();
+    let mut steps = Vec::with_capacity(books.len());
+    while !remaining.is_empty() {
+        let (i, coverage) = remaining
+            .iter()
+            .map(|book| book.coverage(&known))
+            .enumerate()
+            .fold((0, f64::MIN), |best, (i, coverage)| {
+                if coverage > best.1 {
+                    (i, coverage)
+                } else {
+                    best
+                }
+            });
+        let book = remaining.remove(i);
+        let words_to_target = book.words_to_target(&known, target_coverage);
+        known.extend(words_to_target.iter().map(|(word, _)| word.clone()));
+        let learned: Vec<&String> = book
+            .frequencies
+            .iter()
+            .filter(|(word, freq)| **freq >= min_frequency && !known.contains(*word))
+            .map(|(word, _)| word)
+            .collect();
+        steps.push(ReadingStep {
+            title: book.title.clone(),
+            author: book.author.clone(),
+            coverage,
+            words_learned: learned.len(),
+            words_to_target,
+        });
+        known.extend(learned.into_iter().cloned());
+    }
+    steps
+}
+
+/// Tab separated listing of corpus words: word, books, frequency, dispersion
+pub fn corpus_report(words: &[CorpusWord]) -> String {
+    words.iter().fold(String::new(), |s, w| {
+        format!(
+            "{}{}\t{}\t{}\t{:.3}\n",
+            s, w.word, w.books_containing, w.frequency, w.dispersion
+        )
+    })
+}
+
+// Juilland's D based on relative frequencies per part
+fn juilland_d(frequencies: &[u64], part_sizes: &[u64]) -> f64 {
+    let n = frequencies.len();
+    if n < 2 {
+        return 1.0;
+    }
+    let relative: Vec<f64> = frequencies
+        .iter()
+        .zip(part_sizes)
+        .map(|(f, size)| {
+            if *size == 0 {
+                0.0
+            } else {
+                *f as f64 / *size as f64
+            }
+        })
+        .collect();
+    let mean = relative.iter().sum::<f64>() / n as f64;
+    if mean == 0.0 {
+        return 0.0;
+    }
+    let variance = relative.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n as f64;
+    let coefficient_of_variation = variance.sqrt() / mean;
+    (1.0 - coefficient_of_variation / ((n - 1) as f64).sqrt()).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::corpus::{plan_reading_order, BookVocabulary, CorpusIndex};
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+
+    fn book(words: &[&str]) -> BookSegmentation {
+        BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: words.iter().map(|w| w.to_string()).collect(),
+                sentences: vec![],
+            }],
+        }
+    }
+
+    #[test]
+    fn recommend_words_shared_by_most_books() {
+        let books = [
+            book(&["我", "猫", "狗", "狗"]),
+            book(&["我", "猫", "鱼"]),
+            book(&["我", "猫", "狗"]),
+        ];
+        let index = CorpusIndex::build(&books.iter().collect::<Vec<_>>());
+        assert_eq!(index.book_amount, 3);
+        assert_eq!(index.total_words, 10);
+        let known = HashSet::from(["我".to_string()]);
+        let recommended: Vec<String> = index
+            .recommendations(&known, 2)
+            .into_iter()
+            .map(|w| w.word)
+            .collect();
+        assert_eq!(recommended, vec!["猫", "狗"]);
+        let cat = index.words.iter().find(|w| w.word == "猫").unwrap();
+        let fish = index.words.iter().find(|w| w.word == "鱼").unwrap();
+        assert!(cat.dispersion > 0.9);
+        assert!(fish.dispersion < 0.1);
+    }
+
+    #[test]
+    fn plan_easiest_book_first() {
+        let known = HashSet::from(["我".to_string()]);
+        let books = [
+            BookVocabulary::new("难", "甲", &book(&["我", "狗", "鱼", "鸟"])),
+            BookVocabulary::new("易", "乙", &book(&["我", "我", "猫", "猫", "狗", "狗"])),
+        ];
+        let steps = plan_reading_order(&books.iter().collect::<Vec<_>>(), &known, 2, 0.0);
+        let titles: Vec<&str> = steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, vec!["易", "难"]);
+        assert!((steps[0].coverage - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(steps[0].words_learned, 2);
+        // learned 狗 from the first book
+        assert!((steps[1].coverage - 0.5).abs() < 1e-9);
+        assert!(steps.iter().all(|s| s.words_to_target.is_empty()));
+    }
+
+    #[test]
+    fn plan_words_to_reach_target() {
+        let known = HashSet::from(["我".to_string()]);
+        let books = [
+            BookVocabulary::new("难", "甲", &book(&["我", "狗", "鱼", "鱼", "鸟"])),
+            BookVocabulary::new("易", "乙", &book(&["我", "我", "我", "猫"])),
+        ];
+        let steps = plan_reading_order(&books.iter().collect::<Vec<_>>(), &known, 2, 0.7);
+        assert_eq!(steps[0].title, "易");
+        assert!(steps[0].words_to_target.is_empty());
+        // 1 of 5 known: 鱼 lifts it to 3/5, then 狗 (ordered before 鸟) to 4/5
+        assert_eq!(
+            steps[1].words_to_target,
+            vec![("鱼".to_string(), 2), ("狗".to_string(), 1)]
+        );
+    }
+}
@@ -8,22 +8,26 @@ use jieba_rs::Jieba;
 use rusqlite::{params, Connection};
 
 use crate::{
-    config::{get_config, Config},
+    config::{get_config, AnkiLevelsConfig, Config},
     extraction::extract_words,
     fan2jian::get_mapping,
 };
 
-use super::vocab::{db_words_anki_update, VocabStatus};
+use super::vocab::{db_words_anki_update, AnkiWord, VocabStatus};
 
-/// Whether the note is active (i.e one of its cards is) or suspended (all cards are suspended)
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-pub enum NoteStatus {
-    Active,
-    Inactive,
+/// Scheduling state of an Anki card
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Card {
+    pub queue: i64,
+    pub card_type: i64,
+    /// days for review cards, negative seconds for (re)learning cards
+    pub interval: i64,
+    pub lapses: i64,
 }
 
-impl From<i64> for NoteStatus {
-    fn from(value: i64) -> Self {
+impl Card {
+    /// Level of the card, one of the Anki levels of VocabStatus
+    pub fn level(&self, config: &AnkiLevelsConfig) -> VocabStatus {
         // Anki card's queue field value meanings:
         // -- -3=user buried(In scheduler 2),
         // -- -2=sched buried (In scheduler 2),
@@ -32,10 +36,18 @@ impl From<i64> for NoteStatus {
         // -- 0=new, 1=learning, 2=review (as for type)
         // -- 3=in learning, next rev in at least a day after the previous review
         // -- 4=preview
-        if value > 0 {
-            Self::Active
-        } else {
-            Self::Inactive
+        // type: 0=new, 1=learning, 2=review, 3=relearning
+        // buried cards are only hidden until the next day, so their type counts
+        if self.queue == -1 {
+            return VocabStatus::Suspended;
+        }
+        match self.card_type {
+            0 => VocabStatus::New,
+            1 | 3 => VocabStatus::Learning,
+            _ if self.interval >= config.mature_interval && self.lapses < config.leech_lapses => {
+                VocabStatus::Mature
+            }
+            _ => VocabStatus::Young,
         }
     }
 }
@@ -45,8 +57,8 @@ impl From<i64> for NoteStatus {
 pub struct Note {
     /// All fields appended into one string
     pub fields_raw: String,
-    /// Status of the note, which impacts status of contained words
-    pub status: NoteStatus,
+    /// Level of the note's most advanced card, which impacts status of contained words
+    pub status: VocabStatus,
     /// When was the last modification/(un)suspension of the note or one of its cards?
     pub last_modified: i64,
 }
@@ -65,40 +77,41 @@ pub fn db_sync_anki_data(data_conn: &mut Connection) -> Result<()> {
 
     let conn =
         Connection::open_with_flags(anki_db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)?;
-    let all_notes = get_zh_notes(&conn, anki_notes).context("failed to select notes")?;
+    let all_notes = get_zh_notes(&conn, anki_notes, &get_config().anki_levels)
+        .context("failed to select notes")?;
 
     let jieba = Jieba::new();
     let fan2jian = get_mapping(true);
     let jian2fan = get_mapping(false);
-    let mut all_vocab: HashMap<String, VocabStatus> = HashMap::new();
+    let mut all_vocab: HashMap<String, AnkiWord> = HashMap::new();
 
     // extract words from each note and construct vocab
-    // any word that is both active and inactive counts as active
+    // any word that appears in several notes gets the most advanced level
+    // and latest modification of them
     for note in all_notes {
         let words = extract_words(&note.fields_raw, &jieba, &fan2jian, &jian2fan);
-        let vocab_status = VocabStatus::from(note.status);
         // record & update word statuses
         for word in words {
             all_vocab
                 .entry(word)
-                .and_modify(|status| {
-                    // only ever update words that are currently marked inactive
-                    // to ensure any word that appears in active and inactive notes is marked active
-                    if matches!(status, VocabStatus::Inactive) {
-                        *status = vocab_status;
-                    }
+                .and_modify(|anki_word| {
+                    anki_word.status = anki_word.status.max(note.status);
+                    anki_word.last_modified = anki_word.last_modified.max(note.last_modified);
                 })
-                .or_insert(vocab_status);
+                .or_insert(AnkiWord {
+                    status: note.status,
+                    last_modified: note.last_modified,
+                });
         }
     }
 
-    let duration = start_extract.elapsed();
+    let _duration = start_extract.elapsed();
 
     // eprintln!("anki sync extraction duration: {duration:#?}");
 
     let start_insert = Instant::now();
     db_words_anki_update(data_conn, &all_vocab)?;
-    let duration = start_insert.elapsed();
+    let _duration = start_insert.elapsed();
     // eprintln!("anki sync insert duration: {duration:#?}");
     Ok(())
 }
@@ -113,26 +126,30 @@ struct Notetype {
     name: String,
 }
 
-// get (note id, note fields, maximum modfification of note and its active cards, max queue) tuples
-// selecting max queue means obtaining the more "active" status as positive values identify active
-// cards and negative values identify inactive cards
+// get (note id, note fields, modification of note or card, card queue, type, interval, lapses)
+// tuples for every card, grouped by note
 //
 const SELECT_NOTES: &str =
-    "SELECT n.id, n.flds, MAX(COALESCE(n.mod, 0), COALESCE(c.mod, 0)) AS max_mod, MAX(c.queue) AS queue \
+    "SELECT n.id, n.flds, MAX(COALESCE(n.mod, 0), COALESCE(c.mod, 0)) AS max_mod, \
+     c.queue, c.type, c.ivl, c.lapses \
      FROM notes n JOIN cards c ON n.id = c.nid \
      WHERE n.mid = ?1 \
-     GROUP BY n.id, n.flds";
+     ORDER BY n.id";
 
 const SELECT_NOTETYPES_SQL: &str = "SELECT notetypes.id, notetypes.name FROM notetypes";
 
-fn get_zh_notes(conn: &Connection, notetypes: Vec<String>) -> Result<Vec<Note>> {
+fn get_zh_notes(
+    conn: &Connection,
+    notetypes: Vec<String>,
+    config: &AnkiLevelsConfig,
+) -> Result<Vec<Note>> {
     let notetypes = get_zh_notetypes(conn, notetypes)?;
     let mut all_notes: Vec<Note> = Vec::new();
     for Notetype {
         id: notetype_id, ..
     } in notetypes
     {
-        all_notes.extend(select_notes(conn, notetype_id)?);
+        all_notes.extend(select_notes(conn, notetype_id, config)?);
     }
     Ok(all_notes)
 }
@@ -158,16 +175,69 @@ fn get_zh_notetypes(conn: &Connection, zh_notetype_names: Vec<String>) -> Result
     res.context("failed to select notetypes")
 }
 
-fn select_notes(conn: &Connection, notetype_id: i64) -> Result<Vec<Note>, rusqlite::Error> {
+// a note's status is the level of its most advanced card,
+// its modification time the latest of itself and its cards
+fn select_notes(
+    conn: &Connection,
+    notetype_id: i64,
+    config: &AnkiLevelsConfig,
+) -> Result<Vec<Note>, rusqlite::Error> {
     let params = params![notetype_id];
-    conn.prepare(SELECT_NOTES)?
-        .query_map(params, |row| {
-            let status: i64 = row.get(3)?;
-            Ok(Note {
-                fields_raw: row.get(1)?,
-                status: status.into(),
-                last_modified: row.get(2)?,
-            })
-        })?
-        .collect::<Result<Vec<Note>, _>>()
+    let mut stmt = conn.prepare(SELECT_NOTES)?;
+    let cards = stmt.query_map(params, |row| {
+        let note_id: i64 = row.get(0)?;
+        let card = Card {
+            queue: row.get(3)?,
+            card_type: row.get(4)?,
+            interval: row.get(5)?,
+            lapses: row.get(6)?,
+        };
+        Ok((note_id, row.get(1)?, row.get(2)?, card))
+    })?;
+    let mut notes: Vec<(i64, Note)> = Vec::new();
+    for card_row in cards {
+        let (note_id, fields_raw, last_modified, card): (i64, String, i64, Card) = card_row?;
+        let status = card.level(config);
+        match notes.last_mut() {
+            Some((id, note)) if *id == note_id => {
+                note.status = note.status.max(status);
+                note.last_modified = note.last_modified.max(last_modified);
+            }
+            _ => notes.push((
+                note_id,
+                Note {
+                    fields_raw,
+                    status,
+                    last_modified,
+                },
+            )),
+        }
+    }
+    Ok(notes.into_iter().map(|(_, note)| note).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(queue: i64, card_type: i64, interval: i64, lapses: i64) -> Card {
+        Card {
+            queue,
+            card_type,
+            interval,
+            lapses,
+        }
+    }
+
+    #[test]
+    fn card_levels() {
+        let config = AnkiLevelsConfig::default();
+        assert_eq!(card(-1, 2, 100, 0).level(&config), VocabStatus::Suspended);
+        assert_eq!(card(0, 0, 0, 0).level(&config), VocabStatus::New);
+        assert_eq!(card(1, 1, -600, 0).level(&config), VocabStatus::Learning);
+        assert_eq!(card(1, 3, -600, 2).level(&config), VocabStatus::Learning);
+        assert_eq!(card(-2, 2, 5, 0).level(&config), VocabStatus::Young);
+        assert_eq!(card(2, 2, 21, 0).level(&config), VocabStatus::Mature);
+        assert_eq!(card(2, 2, 60, 8).level(&config), VocabStatus::Young);
+    }
 }
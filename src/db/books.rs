@@ -1,36 +1,100 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use rusqlite::{params, Connection};
 
 use crate::segmentation::BookSegmentation;
 
 const INSERT_BOOK_QUERY: &str = "
 INSERT INTO books
-(book_name, author_name, book_json)
-VALUES (?1, ?2, ?3)";
+(book_name, author_name, book_json, content_hash)
+VALUES (?1, ?2, ?3, ?4)";
 
 const SELECT_ALL_BOOKS_QUERY: &str = "
-SELECT book_name, author_name, book_json
+SELECT book_name, author_name, book_json, content_hash
 FROM books";
 
+const UPDATE_BOOK_HASH_QUERY: &str = "
+UPDATE books
+SET content_hash = ?3
+WHERE book_name = ?1 AND author_name = ?2";
+
+const SELECT_BOOK_BY_TITLE_QUERY: &str = "
+SELECT author_name, book_json
+FROM books
+WHERE book_name = ?1 AND (?2 IS NULL OR author_name = ?2)
+ORDER BY author_name";
+
 const DELETE_BOOK_QUERY: &str = "
 DELETE FROM books
 WHERE book_name = ?1 AND author_name = ?2";
 
+/// Select all stored books as (title, author, book, content hash)
+///
+/// the hash of books stored before it was saved on import is computed and saved once
 pub fn db_books_select_all(
     data_conn: &Connection,
-) -> Result<Vec<(String, String, BookSegmentation)>> {
+) -> Result<Vec<(String, String, BookSegmentation, String)>> {
     let mut stmt = data_conn.prepare(SELECT_ALL_BOOKS_QUERY)?;
     let res = stmt
         .query_map([], |row| {
             let title: String = row.get(0)?;
             let author: String = row.get(1)?;
             let book_json: String = row.get(2)?;
+            let book_hash: Option<String> = row.get(3)?;
             let book: BookSegmentation =
                 serde_json::from_str(&book_json).expect("failed to deserialize book");
-            Ok((title, author, book))
+            Ok((title, author, book, book_hash))
+        })?
+        .collect::<Result<Vec<_>, _>>()
+        .context("sql error when selecting all books")?;
+    res.into_iter()
+        .map(|(title, author, book, book_hash)| {
+            let book_hash = match book_hash {
+                Some(book_hash) => book_hash,
+                None => {
+                    let book_hash = book.content_hash();
+                    data_conn.execute(UPDATE_BOOK_HASH_QUERY, params![title, author, book_hash])?;
+                    book_hash
+                }
+            };
+            Ok((title, author, book, book_hash))
+        })
+        .collect()
+}
+
+/// Select the stored book with given title (and author, if given), returns (author, book)
+///
+/// fails if several books match
+pub fn db_books_select_by_title(
+    data_conn: &Connection,
+    title: &str,
+    author: Option<&str>,
+) -> Result<Option<(String, BookSegmentation)>> {
+    let mut stmt = data_conn.prepare(SELECT_BOOK_BY_TITLE_QUERY)?;
+    let mut matches = stmt
+        .query_map(params![title, author], |row| {
+            let author: String = row.get(0)?;
+            let book_json: String = row.get(1)?;
+            Ok((author, book_json))
         })?
-        .collect::<Result<Vec<(String, String, BookSegmentation)>, _>>();
-    res.context("sql error when selecting all books")
+        .collect::<Result<Vec<(String, String)>, _>>()
+        .context("sql error when selecting book")?;
+    if matches.len() > 1 {
+        let authors: Vec<&str> = matches.iter().map(|(author, _)| author.as_str()).collect();
+        bail!(
+            "{} stored books titled {}, specify one of the authors: {}",
+            matches.len(),
+            title,
+            authors.join(", ")
+        );
+    }
+    matches
+        .pop()
+        .map(|(author, book_json)| {
+            let book: BookSegmentation = serde_json::from_str(&book_json)
+                .with_context(|| format!("failed to deserialize stored book {}", title))?;
+            Ok((author, book))
+        })
+        .transpose()
 }
 
 pub fn db_books_insert(
@@ -40,7 +104,10 @@ pub fn db_books_insert(
     book: &BookSegmentation,
 ) -> Result<()> {
     let book_json = serde_json::to_string(book).expect("failed to serialize segmented book");
-    data_conn.execute(INSERT_BOOK_QUERY, params![title, author, book_json])?;
+    data_conn.execute(
+        INSERT_BOOK_QUERY,
+        params![title, author, book_json, book.content_hash()],
+    )?;
     Ok(())
 }
 
@@ -48,3 +115,80 @@ pub fn db_books_delete(data_conn: &Connection, title: &str, author: &str) -> Res
     data_conn.execute(DELETE_BOOK_QUERY, params![title, author])?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::db::books::{db_books_insert, db_books_select_all, db_books_select_by_title};
+    use crate::db::open_test_db;
+    use crate::segmentation::BookSegmentation;
+
+    #[test]
+    fn content_hash_stored_or_filled_in() {
+        let conn = open_test_db();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![],
+        };
+        db_books_insert(&conn, "书", "甲", &book).unwrap();
+        db_books_insert(&conn, "画", "乙", &book).unwrap();
+        conn.execute(
+            "UPDATE books SET content_hash = NULL WHERE book_name = '画'",
+            [],
+        )
+        .unwrap();
+        let books = db_books_select_all(&conn).unwrap();
+        assert!(books
+            .iter()
+            .all(|(_, _, _, hash)| *hash == book.content_hash()));
+        let missing: i64 = conn
+            .query_row(
+                "SELECT count(*) FROM books WHERE content_hash IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(missing, 0);
+    }
+
+    #[test]
+    fn select_by_title_and_author() {
+        let conn = open_test_db();
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![],
+        };
+        db_books_insert(&conn, "书", "甲", &book).unwrap();
+        assert_eq!(
+            db_books_select_by_title(&conn, "书", None)
+                .unwrap()
+                .unwrap()
+                .0,
+            "甲"
+        );
+        db_books_insert(&conn, "书", "乙", &book).unwrap();
+        let err = match db_books_select_by_title(&conn, "书", None) {
+            Err(err) => err,
+            Ok(_) => panic!("ambiguous title selected a book"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "2 stored books titled 书, specify one of the authors: 乙, 甲"
+        );
+        assert_eq!(
+            db_books_select_by_title(&conn, "书", Some("乙"))
+                .unwrap()
+                .unwrap()
+                .0,
+            "乙"
+        );
+        assert!(db_books_select_by_title(&conn, "画", None)
+            .unwrap()
+            .is_none());
+        conn.execute(
+            "UPDATE books SET book_json = 'x' WHERE author_name = '甲'",
+            [],
+        )
+        .unwrap();
+        assert!(db_books_select_by_title(&conn, "书", Some("甲")).is_err());
+    }
+}
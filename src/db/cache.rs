@@ -0,0 +1,100 @@
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+
+const SELECT_VOCAB_VERSION_QUERY: &str = "SELECT version FROM vocab_version";
+const BUMP_VOCAB_VERSION_QUERY: &str = "UPDATE vocab_version SET version = version + 1";
+
+const SELECT_CACHE_QUERY: &str = "
+SELECT value_json
+FROM analysis_cache
+WHERE book_hash = ?1 AND cache_key = ?2 AND vocab_version = ?3";
+
+const INSERT_CACHE_QUERY: &str = "
+INSERT OR REPLACE INTO analysis_cache
+(book_hash, cache_key, vocab_version, value_json)
+VALUES (?1, ?2, ?3, ?4)";
+
+const DELETE_OUTDATED_CACHE_QUERY: &str = "
+DELETE FROM analysis_cache
+WHERE vocab_version < ?1";
+
+/// Current version of the known vocabulary
+pub fn db_vocab_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(SELECT_VOCAB_VERSION_QUERY, [], |row| row.get(0))?)
+}
+
+/// Increase the vocabulary version, dropping cache entries of previous versions
+pub fn db_vocab_version_bump(conn: &Connection) -> Result<()> {
+    conn.execute(BUMP_VOCAB_VERSION_QUERY, params![])?;
+    let version = db_vocab_version(conn)?;
+    conn.execute(DELETE_OUTDATED_CACHE_QUERY, params![version])?;
+    Ok(())
+}
+
+/// Select cached value of a book, None if there is none for the given vocabulary version
+///
+/// values that can not be deserialized (e.g cached by an older release) count as missing
+pub fn db_cache_select<T: DeserializeOwned>(
+    conn: &Connection,
+    book_hash: &str,
+    key: &str,
+    vocab_version: i64,
+) -> Result<Option<T>> {
+    let value_json: Option<String> = conn
+        .query_row(
+            SELECT_CACHE_QUERY,
+            params![book_hash, key, vocab_version],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(value_json.and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+pub fn db_cache_insert<T: Serialize>(
+    conn: &Connection,
+    book_hash: &str,
+    key: &str,
+    vocab_version: i64,
+    value: &T,
+) -> Result<()> {
+    let value_json = serde_json::to_string(value)?;
+    conn.execute(
+        INSERT_CACHE_QUERY,
+        params![book_hash, key, vocab_version, value_json],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::cache::{
+        db_cache_insert, db_cache_select, db_vocab_version, db_vocab_version_bump,
+    };
+    use crate::db::open_test_db;
+
+    #[test]
+    fn cache_entries_expire_with_vocab_version() {
+        let conn = open_test_db();
+        let version = db_vocab_version(&conn).unwrap();
+        db_cache_insert(&conn, "hash", "key", version, &vec![1u64, 2]).unwrap();
+        let cached: Option<Vec<u64>> = db_cache_select(&conn, "hash", "key", version).unwrap();
+        assert_eq!(cached, Some(vec![1, 2]));
+        let other_key: Option<Vec<u64>> = db_cache_select(&conn, "hash", "other", version).unwrap();
+        assert_eq!(other_key, None);
+        // undeserializable values count as missing
+        let wrong_type: Option<String> = db_cache_select(&conn, "hash", "key", version).unwrap();
+        assert_eq!(wrong_type, None);
+
+        db_vocab_version_bump(&conn).unwrap();
+        let new_version = db_vocab_version(&conn).unwrap();
+        assert_eq!(new_version, version + 1);
+        let stale: Option<Vec<u64>> = db_cache_select(&conn, "hash", "key", new_version).unwrap();
+        assert_eq!(stale, None);
+        // outdated entries are evicted, not just hidden
+        let remaining: i64 = conn
+            .query_row("SELECT count(*) FROM analysis_cache", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
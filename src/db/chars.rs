@@ -0,0 +1,91 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use super::cache::db_vocab_version_bump;
+use crate::vocabulary::CharOverrides;
+
+const UPSERT_CHAR_OVERRIDE_QUERY: &str = "INSERT INTO char_overrides (hanzi, known, added_at)
+                                          VALUES (?1, ?2, strftime('%s', 'now'))
+                                          ON CONFLICT(hanzi) DO UPDATE SET
+                                          known = excluded.known, added_at = excluded.added_at
+                                          WHERE known != excluded.known";
+const DELETE_CHAR_OVERRIDE_QUERY: &str = "DELETE FROM char_overrides WHERE hanzi = ?1";
+const SELECT_CHAR_OVERRIDES_QUERY: &str = "SELECT hanzi, known, added_at FROM char_overrides
+                                           ORDER BY hanzi";
+
+/// A character explicitly marked as known or unknown
+pub struct CharOverride {
+    pub hanzi: String,
+    pub known: bool,
+    pub added_at: SystemTime,
+}
+
+/// Mark characters as known or unknown, returns the characters whose override changed (sorted)
+pub fn db_char_overrides_set(
+    conn: &mut Connection,
+    chars: &[&str],
+    known: bool,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let mut changed = Vec::new();
+    for hanzi in chars {
+        if tx.execute(UPSERT_CHAR_OVERRIDE_QUERY, params![hanzi, known])? > 0 {
+            changed.push(hanzi.to_string());
+        }
+    }
+    // cached analysis infos depend on the known characters
+    if !changed.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    changed.sort();
+    changed.dedup();
+    Ok(changed)
+}
+
+/// Remove overrides of characters, returns the characters that had one (sorted)
+pub fn db_char_overrides_del(conn: &mut Connection, chars: &[&str]) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let mut deleted = Vec::new();
+    for hanzi in chars {
+        if tx.execute(DELETE_CHAR_OVERRIDE_QUERY, params![hanzi])? > 0 {
+            deleted.push(hanzi.to_string());
+        }
+    }
+    if !deleted.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    deleted.sort();
+    Ok(deleted)
+}
+
+/// Select all character overrides, sorted by character
+pub fn db_char_overrides_select_all(conn: &Connection) -> Result<Vec<CharOverride>> {
+    let mut stmt = conn.prepare(SELECT_CHAR_OVERRIDES_QUERY)?;
+    let overrides = stmt
+        .query_map([], |row| {
+            let added_at: u64 = row.get(2)?;
+            Ok(CharOverride {
+                hanzi: row.get(0)?,
+                known: row.get(1)?,
+                added_at: SystemTime::UNIX_EPOCH + Duration::from_secs(added_at),
+            })
+        })?
+        .collect::<std::result::Result<Vec<CharOverride>, _>>()?;
+    Ok(overrides)
+}
+
+pub fn db_char_overrides_select(conn: &Connection) -> Result<CharOverrides> {
+    let mut char_overrides = CharOverrides::default();
+    for char_override in db_char_overrides_select_all(conn)? {
+        if char_override.known {
+            char_overrides.known.insert(char_override.hanzi);
+        } else {
+            char_overrides.unknown.insert(char_override.hanzi);
+        }
+    }
+    Ok(char_overrides)
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use crate::{
+    history::{StatusCounts, VocabSnapshot},
+    vocabulary::VocabularyInfo,
+};
+
+const INSERT_SNAPSHOT_QUERY: &str = "
+INSERT INTO vocab_history
+(time, words_known, words_active, words_inactive, chars_known, chars_active, chars_inactive,
+ words_suspended, words_new, words_learning, words_young, words_mature, words_external, known_levels)
+VALUES (strftime('%s', 'now'), ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)";
+
+const SELECT_ALL_SNAPSHOTS_QUERY: &str = "
+SELECT time, words_known, words_active, words_inactive, chars_known, chars_active, chars_inactive,
+       words_suspended, words_new, words_learning, words_young, words_mature, words_external, known_levels
+FROM vocab_history
+ORDER BY time, id";
+
+pub fn db_history_insert(
+    conn: &Connection,
+    info: &VocabularyInfo,
+    status_counts: &StatusCounts,
+    known_levels: &str,
+) -> Result<()> {
+    conn.execute(
+        INSERT_SNAPSHOT_QUERY,
+        params![
+            info.words_known,
+            info.words_active,
+            info.words_inactive,
+            info.chars_known,
+            info.chars_active,
+            info.chars_inactive,
+            status_counts.suspended,
+            status_counts.new,
+            status_counts.learning,
+            status_counts.young,
+            status_counts.mature,
+            status_counts.external,
+            known_levels
+        ],
+    )?;
+    Ok(())
+}
+
+/// Select all snapshots, oldest first
+pub fn db_history_select_all(conn: &Connection) -> Result<Vec<VocabSnapshot>> {
+    let mut stmt = conn.prepare(SELECT_ALL_SNAPSHOTS_QUERY)?;
+    let snapshots = stmt
+        .query_map([], |row| {
+            let time = SystemTime::UNIX_EPOCH + Duration::from_secs(row.get(0)?);
+            // snapshots taken before counts per status were kept have none
+            let status_counts = match row.get::<_, Option<usize>>(7)? {
+                Some(suspended) => Some(StatusCounts {
+                    suspended,
+                    new: row.get(8)?,
+                    learning: row.get(9)?,
+                    young: row.get(10)?,
+                    mature: row.get(11)?,
+                    external: row.get(12)?,
+                }),
+                None => None,
+            };
+            Ok(VocabSnapshot {
+                time,
+                info: VocabularyInfo {
+                    words_known: row.get(1)?,
+                    words_active: row.get(2)?,
+                    words_inactive: row.get(3)?,
+                    chars_known: row.get(4)?,
+                    chars_active: row.get(5)?,
+                    chars_inactive: row.get(6)?,
+                },
+                status_counts,
+                known_levels: row.get(13)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<VocabSnapshot>, _>>()?;
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::history::{db_history_insert, db_history_select_all};
+    use crate::db::open_test_db;
+    use crate::history::StatusCounts;
+    use crate::vocabulary::VocabularyInfo;
+
+    #[test]
+    fn keep_counts_per_status_and_known_levels() {
+        let conn = open_test_db();
+        conn.execute(
+            "INSERT INTO vocab_history
+             (time, words_known, words_active, words_inactive, chars_known, chars_active, chars_inactive)
+             VALUES (0, 1, 1, 0, 1, 1, 0)",
+            [],
+        )
+        .unwrap();
+        let info = VocabularyInfo {
+            words_known: 3,
+            words_active: 2,
+            words_inactive: 1,
+            chars_known: 2,
+            chars_active: 2,
+            chars_inactive: 0,
+        };
+        let counts = StatusCounts {
+            suspended: 0,
+            new: 1,
+            learning: 0,
+            young: 1,
+            mature: 1,
+            external: 1,
+        };
+        db_history_insert(&conn, &info, &counts, "young,mature").unwrap();
+        let history = db_history_select_all(&conn).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].status_counts, None);
+        assert_eq!(history[0].known_levels, None);
+        assert_eq!(history[1].status_counts, Some(counts));
+        assert_eq!(history[1].known_levels.as_deref(), Some("young,mature"));
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use super::{cache::db_vocab_version_bump, vocab::ExternalSource};
+
+const INSERT_IGNORED_WORD_QUERY: &str =
+    "INSERT OR IGNORE INTO words_ignored (word, added_at, source)
+                                         VALUES (?1, strftime('%s', 'now'), ?2)";
+const DELETE_IGNORED_WORD_QUERY: &str = "DELETE FROM words_ignored WHERE word = ?1";
+const SELECT_IGNORED_WORDS_QUERY: &str = "SELECT word, added_at, source FROM words_ignored
+                                          ORDER BY added_at, word";
+
+/// A word of the global ignore list
+pub struct IgnoredWord {
+    pub word: String,
+    pub added_at: SystemTime,
+    pub source: ExternalSource,
+}
+
+/// Add words to the ignore list, returns the words that were not ignored before (sorted)
+///
+/// exclude_from_counts: whether ignored words are left out of counts,
+/// only then cached analysis infos are outdated by the change
+pub fn db_ignored_add(
+    conn: &mut Connection,
+    words: &HashSet<&str>,
+    source: &ExternalSource,
+    exclude_from_counts: bool,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let source = source.to_string();
+    let mut added = Vec::new();
+    for word in words {
+        if tx.execute(INSERT_IGNORED_WORD_QUERY, params![word, source])? > 0 {
+            added.push(word.to_string());
+        }
+    }
+    if exclude_from_counts && !added.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    added.sort();
+    Ok(added)
+}
+
+/// Remove words from the ignore list, returns the words that were actually removed (sorted)
+///
+/// exclude_from_counts: as for db_ignored_add
+pub fn db_ignored_del(
+    conn: &mut Connection,
+    words: &HashSet<String>,
+    exclude_from_counts: bool,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let mut deleted = Vec::new();
+    for word in words {
+        if tx.execute(DELETE_IGNORED_WORD_QUERY, params![word])? > 0 {
+            deleted.push(word.clone());
+        }
+    }
+    if exclude_from_counts && !deleted.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    deleted.sort();
+    Ok(deleted)
+}
+
+/// Select all ignored words, oldest first
+pub fn db_ignored_select_all(conn: &Connection) -> Result<Vec<IgnoredWord>> {
+    let mut stmt = conn.prepare(SELECT_IGNORED_WORDS_QUERY)?;
+    let words = stmt
+        .query_map([], |row| {
+            let added_at: u64 = row.get(1)?;
+            let source: String = row.get(2)?;
+            Ok(IgnoredWord {
+                word: row.get(0)?,
+                added_at: SystemTime::UNIX_EPOCH + Duration::from_secs(added_at),
+                source: source.parse().unwrap_or(ExternalSource::Unknown),
+            })
+        })?
+        .collect::<std::result::Result<Vec<IgnoredWord>, _>>()?;
+    Ok(words)
+}
+
+pub fn db_ignored_select_words(conn: &Connection) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT word FROM words_ignored")?;
+    let words = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<HashSet<String>, _>>()?;
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::db::cache::db_vocab_version;
+    use crate::db::ignore::{
+        db_ignored_add, db_ignored_del, db_ignored_select_all, db_ignored_select_words,
+    };
+    use crate::db::open_test_db;
+    use crate::db::vocab::ExternalSource;
+
+    #[test]
+    fn add_and_remove_ignored_words() {
+        let mut conn = open_test_db();
+        let source = ExternalSource::File("ignore.txt".to_string());
+        let added = db_ignored_add(&mut conn, &HashSet::from(["猫", "狗"]), &source, true).unwrap();
+        assert_eq!(added, vec!["狗", "猫"]);
+        let added = db_ignored_add(&mut conn, &HashSet::from(["猫", "鱼"]), &source, true).unwrap();
+        assert_eq!(added, vec!["鱼"]);
+        let ignored = db_ignored_select_all(&conn).unwrap();
+        assert_eq!(ignored.len(), 3);
+        assert!(ignored.iter().all(|word| word.source == source));
+
+        let to_delete = HashSet::from(["猫".to_string(), "鸟".to_string()]);
+        assert_eq!(
+            db_ignored_del(&mut conn, &to_delete, true).unwrap(),
+            vec!["猫"]
+        );
+        assert_eq!(
+            db_ignored_select_words(&conn).unwrap(),
+            HashSet::from(["狗".to_string(), "鱼".to_string()])
+        );
+    }
+
+    #[test]
+    fn bump_version_only_when_excluded_from_counts() {
+        let mut conn = open_test_db();
+        let version = db_vocab_version(&conn).unwrap();
+        let words = HashSet::from(["猫"]);
+        db_ignored_add(&mut conn, &words, &ExternalSource::Manual, false).unwrap();
+        assert_eq!(db_vocab_version(&conn).unwrap(), version);
+        db_ignored_del(&mut conn, &HashSet::from(["猫".to_string()]), true).unwrap();
+        assert_eq!(db_vocab_version(&conn).unwrap(), version + 1);
+        // nothing changed, nothing to invalidate
+        db_ignored_del(&mut conn, &HashSet::from(["猫".to_string()]), true).unwrap();
+        assert_eq!(db_vocab_version(&conn).unwrap(), version + 1);
+    }
+}
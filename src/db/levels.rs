@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+use super::cache::db_vocab_version_bump;
+use crate::levels::WordLevel;
+
+const DELETE_LEVELS_OF_SOURCE_QUERY: &str = "DELETE FROM word_levels WHERE source = ?1";
+const INSERT_LEVEL_QUERY: &str = "INSERT OR REPLACE INTO word_levels (word, level, source)
+                                  VALUES (?1, ?2, ?3)";
+const INSERT_SOURCE_QUERY: &str = "INSERT OR IGNORE INTO level_sources (source, active)
+                                   VALUES (?1, 0)";
+const ACTIVATE_SOURCE_QUERY: &str = "UPDATE level_sources SET active = (source = ?1)";
+const SELECT_SOURCES_QUERY: &str = "SELECT level_sources.source, active, count(word)
+                                    FROM level_sources
+                                    LEFT JOIN word_levels ON word_levels.source = level_sources.source
+                                    GROUP BY level_sources.source
+                                    ORDER BY level_sources.source";
+const SELECT_ACTIVE_LEVELS_QUERY: &str = "SELECT word, level FROM word_levels
+                                          WHERE source IN
+                                          (SELECT source FROM level_sources WHERE active = 1)";
+
+const DELETE_RANKS_QUERY: &str = "DELETE FROM word_ranks";
+const INSERT_RANK_QUERY: &str = "INSERT OR REPLACE INTO word_ranks (word, rank)
+                                 VALUES (?1, ?2)";
+
+/// Insert word levels of a source (e.g hsk3), replacing previous levels of that source,
+/// and make it the active source
+///
+/// if clear_source is false, previous levels of the source are kept
+/// (for sources that are imported from one file per level)
+pub fn db_levels_insert(
+    conn: &mut Connection,
+    levels: &HashMap<String, u8>,
+    source: &str,
+    clear_source: bool,
+) -> Result<()> {
+    let tx = conn.transaction()?;
+    if clear_source {
+        tx.execute(DELETE_LEVELS_OF_SOURCE_QUERY, params![source])?;
+    }
+    for (word, level) in levels {
+        tx.execute(INSERT_LEVEL_QUERY, params![word, level, source])?;
+    }
+    tx.execute(INSERT_SOURCE_QUERY, params![source])?;
+    tx.execute(ACTIVATE_SOURCE_QUERY, params![source])?;
+    // cached analysis results depend on levels and ranks as well
+    db_vocab_version_bump(&tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Delete all previous frequency ranks and insert given ones
+pub fn db_ranks_update(conn: &mut Connection, ranks: &HashMap<String, u64>) -> Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute(DELETE_RANKS_QUERY, params![])?;
+    for (word, rank) in ranks {
+        tx.execute(INSERT_RANK_QUERY, params![word, rank])?;
+    }
+    db_vocab_version_bump(&tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Make the levels of an imported source the ones used, returns false if there is no such source
+pub fn db_levels_activate(conn: &mut Connection, source: &str) -> Result<bool> {
+    let tx = conn.transaction()?;
+    let exists = tx.query_row(
+        "SELECT count(*) FROM level_sources WHERE source = ?1",
+        params![source],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if exists {
+        tx.execute(ACTIVATE_SOURCE_QUERY, params![source])?;
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    Ok(exists)
+}
+
+/// All imported level sources as (source, whether active, amount of words), sorted by source
+pub fn db_levels_select_sources(conn: &Connection) -> Result<Vec<(String, bool, usize)>> {
+    let mut stmt = conn.prepare(SELECT_SOURCES_QUERY)?;
+    let sources = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<std::result::Result<Vec<(String, bool, usize)>, _>>()?;
+    Ok(sources)
+}
+
+/// Levels of the active source and frequency ranks
+pub fn db_levels_select_all(conn: &Connection) -> Result<HashMap<String, WordLevel>> {
+    let mut levels: HashMap<String, WordLevel> = HashMap::new();
+    let mut stmt = conn.prepare(SELECT_ACTIVE_LEVELS_QUERY)?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))? {
+        let (word, level) = row?;
+        levels.entry(word).or_default().level = Some(level);
+    }
+    let mut stmt = conn.prepare("SELECT word, rank FROM word_ranks")?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))? {
+        let (word, rank) = row?;
+        levels.entry(word).or_default().rank = Some(rank);
+    }
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::db::embedded::migrations;
+    use crate::db::levels::{
+        db_levels_activate, db_levels_insert, db_levels_select_all, db_levels_select_sources,
+    };
+    use crate::db::{db_migrate, open_test_db};
+    use refinery::Target;
+    use rusqlite::Connection;
+
+    #[test]
+    fn sources_coexist_and_one_is_active() {
+        let mut conn = open_test_db();
+        let hsk2 = HashMap::from([("爱".to_string(), 1), ("爱好".to_string(), 3)]);
+        let hsk3 = HashMap::from([("爱".to_string(), 1), ("爱好".to_string(), 2)]);
+        db_levels_insert(&mut conn, &hsk2, "hsk2", true).unwrap();
+        db_levels_insert(&mut conn, &hsk3, "hsk3", true).unwrap();
+        let sources = db_levels_select_sources(&conn).unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                ("hsk2".to_string(), false, 2),
+                ("hsk3".to_string(), true, 2)
+            ]
+        );
+        let levels = db_levels_select_all(&conn).unwrap();
+        assert_eq!(levels["爱好"].level, Some(2));
+
+        assert!(db_levels_activate(&mut conn, "hsk2").unwrap());
+        assert!(!db_levels_activate(&mut conn, "hsk9").unwrap());
+        let levels = db_levels_select_all(&conn).unwrap();
+        assert_eq!(levels["爱好"].level, Some(3));
+    }
+
+    #[test]
+    fn most_recently_imported_source_stays_active() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrations::runner()
+            .set_target(Target::Version(14))
+            .run(&mut conn)
+            .unwrap();
+        // sources imported before levels were kept per source, hsk2 last
+        conn.execute_batch(
+            "INSERT INTO word_levels (word, level, source) VALUES ('爱', 1, 'hsk3');
+             INSERT INTO word_levels (word, level, source) VALUES ('爱好', 2, 'hsk3');
+             INSERT OR REPLACE INTO word_levels (word, level, source) VALUES ('爱', 1, 'hsk2');",
+        )
+        .unwrap();
+        db_migrate(&mut conn).unwrap();
+        let sources = db_levels_select_sources(&conn).unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                ("hsk2".to_string(), true, 1),
+                ("hsk3".to_string(), false, 1)
+            ]
+        );
+    }
+}
@@ -1,4 +1,31 @@
 pub mod anki;
 pub mod books;
+pub mod cache;
+pub mod chars;
+pub mod history;
+pub mod ignore;
+pub mod levels;
 pub mod vocab;
 pub mod word_lists;
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+mod embedded {
+    use refinery::embed_migrations;
+    embed_migrations!("migrations_sql");
+}
+
+/// Bring the database schema up to date
+pub fn db_migrate(conn: &mut Connection) -> Result<()> {
+    embedded::migrations::runner().run(conn)?;
+    Ok(())
+}
+
+/// Fresh in-memory database with all migrations applied
+#[cfg(test)]
+pub(crate) fn open_test_db() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    db_migrate(&mut conn).unwrap();
+    conn
+}
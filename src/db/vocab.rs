@@ -1,130 +1,412 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rusqlite::{params, Connection};
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
-use super::anki::NoteStatus;
+use super::cache::db_vocab_version_bump;
 
 // vocabulary
 const DELETE_ANKI_WORDS_QUERY: &str = "DELETE FROM words_anki";
-const INSERT_ANKI_WORD_QUERY: &str = "INSERT INTO words_anki (word, status)
-                                    VALUES (?1, ?2)";
+const INSERT_ANKI_WORD_QUERY: &str = "INSERT INTO words_anki (word, status, last_modified)
+                                    VALUES (?1, ?2, ?3)";
 
-const INSERT_EXT_WORD_QUERY: &str = "INSERT OR IGNORE INTO words_external (word, status)
-                                 VALUES (?1, ?2))";
+const INSERT_EXT_WORD_QUERY: &str =
+    "INSERT OR IGNORE INTO words_external (word, added_at, source, note)
+                                     VALUES (?1, strftime('%s', 'now'), ?2, ?3)";
 const DELETE_EXT_WORD_QUERY: &str = "DELETE FROM words_external WHERE word = ?1";
+const SELECT_EXT_WORDS_QUERY: &str = "SELECT word, added_at, source, note FROM words_external
+                                      ORDER BY added_at, word";
+const SELECT_EXT_WORDS_OF_SOURCE_QUERY: &str = "SELECT word FROM words_external WHERE source = ?1";
+const DELETE_EXT_WORDS_OF_SOURCE_QUERY: &str = "DELETE FROM words_external WHERE source = ?1";
 
-const STATUS_ACTIVE: i64 = 0;
-const STATUS_INACTIVE: i64 = 1;
+const STATUS_NEW: i64 = 0;
+const STATUS_LEARNING: i64 = 1;
+const STATUS_YOUNG: i64 = 2;
+const STATUS_MATURE: i64 = 3;
+const STATUS_SUSPENDED: i64 = 4;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+/// Status of a word, for Anki words the level of its most advanced card
+///
+/// ordered from least to most advanced, added external words come last
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum VocabStatus {
-    Active,
-    Inactive,
+    Suspended,
+    New,
+    Learning,
+    Young,
+    Mature,
     AddedExternal,
 }
 
 impl VocabStatus {
+    /// Anki levels, from least to most advanced
+    pub const ANKI_LEVELS: [VocabStatus; 5] = [
+        VocabStatus::Suspended,
+        VocabStatus::New,
+        VocabStatus::Learning,
+        VocabStatus::Young,
+        VocabStatus::Mature,
+    ];
+
     fn from_i64(i: i64) -> Self {
         match i {
-            STATUS_ACTIVE => VocabStatus::Active,
-            STATUS_INACTIVE => VocabStatus::Inactive,
+            STATUS_NEW => VocabStatus::New,
+            STATUS_LEARNING => VocabStatus::Learning,
+            STATUS_YOUNG => VocabStatus::Young,
+            STATUS_MATURE => VocabStatus::Mature,
+            STATUS_SUSPENDED => VocabStatus::Suspended,
             _ => unreachable!(),
         }
     }
 
     fn to_i64(self) -> i64 {
         match self {
-            VocabStatus::Active => STATUS_ACTIVE,
-            VocabStatus::Inactive => STATUS_INACTIVE,
+            VocabStatus::New => STATUS_NEW,
+            VocabStatus::Learning => STATUS_LEARNING,
+            VocabStatus::Young => STATUS_YOUNG,
+            VocabStatus::Mature => STATUS_MATURE,
+            VocabStatus::Suspended => STATUS_SUSPENDED,
             VocabStatus::AddedExternal => unreachable!(),
         }
     }
+
+    /// Whether a word of this status counts as known, added external words always do
+    pub fn is_known(self, known_levels: &[VocabStatus]) -> bool {
+        self == VocabStatus::AddedExternal || known_levels.contains(&self)
+    }
+}
+
+impl Display for VocabStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            VocabStatus::Suspended => "suspended",
+            VocabStatus::New => "new",
+            VocabStatus::Learning => "learning",
+            VocabStatus::Young => "young",
+            VocabStatus::Mature => "mature",
+            VocabStatus::AddedExternal => "added-external",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for VocabStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ANKI_LEVELS
+            .into_iter()
+            .chain([VocabStatus::AddedExternal])
+            .find(|status| status.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown status {}, use suspended, new, learning, young, mature or added-external",
+                    s
+                )
+            })
+    }
+}
+
+/// Known levels as comma separated list, e.g learning,young,mature
+pub fn describe_known_levels(known_levels: &[VocabStatus]) -> String {
+    let mut levels = known_levels.to_vec();
+    levels.sort();
+    levels.dedup();
+    levels
+        .iter()
+        .map(|level| level.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Parse comma separated levels, e.g young,mature
+pub fn parse_known_levels(s: &str) -> Result<Vec<VocabStatus>> {
+    s.split(',')
+        .filter(|level| !level.trim().is_empty())
+        .map(VocabStatus::from_str)
+        .collect()
+}
+
+/// Where externally added words come from
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum ExternalSource {
+    /// file name of a word file
+    File(String),
+    Manual,
+    /// name of the word list the words were marked known in
+    WordList(String),
+    /// added before sources were recorded
+    Unknown,
+}
+
+// also the representation in the DB
+impl Display for ExternalSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalSource::File(name) => write!(f, "file:{}", name),
+            ExternalSource::Manual => write!(f, "manual"),
+            ExternalSource::WordList(name) => write!(f, "word-list:{}", name),
+            ExternalSource::Unknown => write!(f, "unknown"),
+        }
+    }
 }
 
-impl From<NoteStatus> for VocabStatus {
-    fn from(status: NoteStatus) -> Self {
-        match status {
-            NoteStatus::Active => VocabStatus::Active,
-            NoteStatus::Inactive => VocabStatus::Inactive,
+impl FromStr for ExternalSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Some(name) = s.strip_prefix("file:") {
+            return Ok(ExternalSource::File(name.to_string()));
+        }
+        if let Some(name) = s.strip_prefix("word-list:") {
+            return Ok(ExternalSource::WordList(name.to_string()));
+        }
+        match s {
+            "manual" => Ok(ExternalSource::Manual),
+            "unknown" => Ok(ExternalSource::Unknown),
+            _ => Err(anyhow!(
+                "invalid source {}, use file:<name>, word-list:<name>, manual or unknown",
+                s
+            )),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExternalWord {
+    pub word: String,
+    /// None for words added before the time was recorded
+    pub added_at: Option<SystemTime>,
+    pub source: ExternalSource,
+    pub note: Option<String>,
+}
+
+/// Status of a word in Anki and the latest modification of the notes it occurs in
+#[derive(Debug, Clone, Copy)]
 pub struct AnkiWord {
+    pub status: VocabStatus,
+    /// seconds since epoch
+    pub last_modified: i64,
+}
+
+/// A word of the vocabulary with its status and where it comes from
+pub struct VocabWord {
     pub word: String,
-    pub status: NoteStatus,
+    pub status: VocabStatus,
+    /// whether the word was synced from Anki
+    pub in_anki: bool,
+    /// latest modification of the word's Anki notes, unknown for words not synced since it is recorded
+    pub anki_modified: Option<SystemTime>,
+    /// provenance of the word if it was added externally
+    pub external: Option<ExternalWord>,
 }
 
-pub fn db_words_external_add(conn: &Connection, words: &HashSet<&str>) -> Result<()> {
+impl VocabWord {
+    pub fn source(&self) -> String {
+        match (self.in_anki, &self.external) {
+            (true, Some(external)) => format!("anki, {}", external.source),
+            (false, Some(external)) => external.source.to_string(),
+            _ => "anki".to_string(),
+        }
+    }
+}
+
+/// Add external words, returns the words that were not added externally before (sorted)
+pub fn db_words_external_add(
+    conn: &mut Connection,
+    words: &HashSet<&str>,
+    source: &ExternalSource,
+    note: Option<&str>,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let source = source.to_string();
+    let mut added = Vec::new();
     for word in words {
-        conn.execute(INSERT_EXT_WORD_QUERY, params![word])?;
+        if tx.execute(INSERT_EXT_WORD_QUERY, params![word, source, note])? > 0 {
+            added.push(word.to_string());
+        }
     }
-    Ok(())
+    if !added.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    added.sort();
+    Ok(added)
 }
 
-pub fn db_words_external_del(conn: &Connection, words: &HashSet<String>) -> Result<()> {
+/// Delete external words, returns the words that were actually deleted (sorted)
+pub fn db_words_external_del(
+    conn: &mut Connection,
+    words: &HashSet<String>,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let mut deleted = Vec::new();
     for word in words {
-        conn.execute(DELETE_EXT_WORD_QUERY, params![word])?;
+        if tx.execute(DELETE_EXT_WORD_QUERY, params![word])? > 0 {
+            deleted.push(word.clone());
+        }
     }
-    Ok(())
+    if !deleted.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    deleted.sort();
+    Ok(deleted)
 }
 
-/// Delete all previous Anki words and insert given set
+/// Delete all external words added from source, returns the deleted words (sorted)
+pub fn db_words_external_del_source(
+    conn: &mut Connection,
+    source: &ExternalSource,
+) -> Result<Vec<String>> {
+    let tx = conn.transaction()?;
+    let source = source.to_string();
+    let mut deleted = tx
+        .prepare(SELECT_EXT_WORDS_OF_SOURCE_QUERY)?
+        .query_map(params![source], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<String>, _>>()?;
+    tx.execute(DELETE_EXT_WORDS_OF_SOURCE_QUERY, params![source])?;
+    if !deleted.is_empty() {
+        db_vocab_version_bump(&tx)?;
+    }
+    tx.commit()?;
+    deleted.sort();
+    Ok(deleted)
+}
+
+/// Select all external words, oldest first
+pub fn db_words_external_select_all(conn: &Connection) -> Result<Vec<ExternalWord>> {
+    let mut stmt = conn.prepare(SELECT_EXT_WORDS_QUERY)?;
+    let words = stmt
+        .query_map([], |row| {
+            let added_at: Option<u64> = row.get(1)?;
+            let source: String = row.get(2)?;
+            Ok(ExternalWord {
+                word: row.get(0)?,
+                added_at: added_at.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+                source: source.parse().unwrap_or(ExternalSource::Unknown),
+                note: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<ExternalWord>, _>>()?;
+    Ok(words)
+}
+
+/// Delete all previous Anki words and insert given set, bumps the vocabulary version
 pub fn db_words_anki_update(
     conn: &mut Connection,
-    vocab: &HashMap<String, VocabStatus>,
+    vocab: &HashMap<String, AnkiWord>,
 ) -> Result<()> {
     let tx = conn.transaction()?;
     tx.execute(DELETE_ANKI_WORDS_QUERY, params![])?;
-    for (word, status) in vocab {
-        let status_int = status.to_i64();
-        tx.execute(INSERT_ANKI_WORD_QUERY, params![word, status_int])?;
+    for (word, anki_word) in vocab {
+        let status_int = anki_word.status.to_i64();
+        tx.execute(
+            INSERT_ANKI_WORD_QUERY,
+            params![word, status_int, anki_word.last_modified],
+        )?;
     }
+    db_vocab_version_bump(&tx)?;
     tx.commit()?;
     Ok(())
 }
 
-pub fn db_words_select_all(conn: &Connection) -> Result<HashMap<String, VocabStatus>> {
-    let mut stmt = conn.prepare("SELECT * FROM words_anki")?;
-    let mut words: HashMap<String, VocabStatus> = stmt
+// Anki words with their level
+fn select_anki_words(conn: &Connection) -> Result<HashMap<String, VocabStatus>> {
+    let mut stmt = conn.prepare("SELECT word, status FROM words_anki")?;
+    let words = stmt
         .query_map([], |row| {
             let word: String = row.get(0)?;
             let status: i64 = row.get(1)?;
             Ok((word, VocabStatus::from_i64(status)))
         })?
         .collect::<std::result::Result<HashMap<String, VocabStatus>, _>>()?;
+    Ok(words)
+}
+
+fn select_external_words(conn: &Connection) -> Result<HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT word FROM words_external")?;
+    let words = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<HashSet<String>, _>>()?;
+    Ok(words)
+}
 
-    let mut stmt = conn.prepare("SELECT * FROM words_external")?;
-    for word_external in stmt.query_map([], |row| {
-        let word: String = row.get(0)?;
-        Ok(word)
-    })? {
+/// Select all words with their status
+///
+/// added external overrides Anki levels that are not known (per config), but not known ones
+pub fn db_words_select_all(
+    conn: &Connection,
+    known_levels: &[VocabStatus],
+) -> Result<HashMap<String, VocabStatus>> {
+    let mut words = select_anki_words(conn)?;
+    for word_external in select_external_words(conn)? {
         words
-            .entry(word_external?)
+            .entry(word_external)
             .and_modify(|status| {
-                // added external overrides inactive, but not active
-                // i.e word that is both inactive and added external counts as known
-                if matches!(status, VocabStatus::Inactive) {
+                // i.e word that is both suspended and added external counts as known
+                if !status.is_known(known_levels) {
                     *status = VocabStatus::AddedExternal;
                 }
             })
             .or_insert(VocabStatus::AddedExternal);
     }
-
     Ok(words)
 }
 
-pub fn db_words_select_known(conn: &Connection) -> Result<HashSet<String>> {
-    let vocab = db_words_select_all(conn)?;
-    Ok(vocab
+/// Select all words with status, Anki modification and external provenance, sorted by word
+pub fn db_words_select_detailed(
+    conn: &Connection,
+    known_levels: &[VocabStatus],
+) -> Result<Vec<VocabWord>> {
+    let mut external: HashMap<String, ExternalWord> = db_words_external_select_all(conn)?
+        .into_iter()
+        .map(|word| (word.word.clone(), word))
+        .collect();
+    // all Anki words, with their modification if recorded
+    let mut stmt = conn.prepare("SELECT word, last_modified FROM words_anki")?;
+    let anki_modified = stmt
+        .query_map([], |row| {
+            let word: String = row.get(0)?;
+            let secs: Option<u64> = row.get(1)?;
+            Ok((
+                word,
+                secs.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ))
+        })?
+        .collect::<std::result::Result<HashMap<String, Option<SystemTime>>, _>>()?;
+    let mut words: Vec<VocabWord> = db_words_select_all(conn, known_levels)?
         .into_iter()
-        .filter_map(|(word, status)| {
-            if !matches!(status, VocabStatus::Inactive) {
-                Some(word)
-            } else {
-                None
-            }
+        .map(|(word, status)| VocabWord {
+            // external words overriding an unknown Anki level are still in Anki
+            in_anki: anki_modified.contains_key(&word),
+            anki_modified: anki_modified.get(&word).copied().flatten(),
+            external: external.remove(&word),
+            word,
+            status,
         })
-        .collect())
+        .collect();
+    words.sort_by(|a, b| a.word.cmp(&b.word));
+    Ok(words)
+}
+
+/// Select words of the known levels and all added external words
+pub fn db_words_select_known(
+    conn: &Connection,
+    known_levels: &[VocabStatus],
+) -> Result<HashSet<String>> {
+    let mut known = select_external_words(conn)?;
+    known.extend(
+        select_anki_words(conn)?
+            .into_iter()
+            .filter(|(_, status)| status.is_known(known_levels))
+            .map(|(word, _)| word),
+    );
+    Ok(known)
 }
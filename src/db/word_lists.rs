@@ -6,13 +6,14 @@ use rusqlite::{params, Connection};
 
 use crate::{
     analysis::AnalysisQuery,
+    filter::FilterExpr,
     word_lists::{ChapterWords, WordList, WordListMetadata},
 };
 
 const INSERT_WORD_LIST_QUERY: &str = "
 INSERT INTO word_lists
-(book_name, author_name, create_time, min_occurrence_words, min_occurrence_chars, word_list_json)
-VALUES (?1, ?2, strftime('%s', 'now'), ?3, ?4, ?5)";
+(book_name, author_name, create_time, min_occurrence_words, min_occurrence_chars, coverage_target, character_focused, max_level, library_wide, exclude_guessable, filter, word_list_json)
+VALUES (?1, ?2, strftime('%s', 'now'), ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
 
 const UPDATE_WORD_LIST_QUERY: &str = "
 UPDATE word_lists
@@ -20,11 +21,11 @@ SET word_list_json = ?1
 WHERE id = ?2";
 
 const SELECT_ALL_WORD_LISTS_QUERY: &str = "
-SELECT id, book_name, author_name, create_time, min_occurrence_words, min_occurrence_chars
+SELECT id, book_name, author_name, create_time, min_occurrence_words, min_occurrence_chars, coverage_target, character_focused, max_level, library_wide, exclude_guessable, filter
 FROM word_lists";
 
 const SELECT_WORD_LIST_QUERY: &str = "
-SELECT (word_list_json)
+SELECT word_list_json
 FROM word_lists WHERE id = ?1";
 
 const DELETE_WORD_LIST_QUERY: &str = "
@@ -39,6 +40,17 @@ pub fn db_wlist_insert(conn: &Connection, word_list: WordList) -> Result<()> {
         .metadata
         .analysis_query
         .min_occurrence_unknown_chars;
+    let max_level = word_list.metadata.analysis_query.max_level;
+    let exclude_guessable = word_list.metadata.analysis_query.exclude_guessable;
+    let filter = word_list
+        .metadata
+        .analysis_query
+        .filter
+        .as_ref()
+        .map(|filter| filter.source().to_string());
+    let coverage_target = word_list.metadata.coverage_target;
+    let character_focused = word_list.metadata.character_focused;
+    let library_wide = word_list.metadata.library_wide;
     let word_list_json = serde_json::to_string(&word_list.words_per_chapter)
         .expect("failed to serialize words per chapter lists");
     conn.execute(
@@ -48,6 +60,12 @@ pub fn db_wlist_insert(conn: &Connection, word_list: WordList) -> Result<()> {
             author_name,
             min_occ_words,
             min_occ_chars,
+            coverage_target,
+            character_focused,
+            max_level,
+            library_wide,
+            exclude_guessable,
+            filter,
             word_list_json
         ],
     )?;
@@ -66,20 +84,27 @@ pub fn db_wlist_select_all_mdata(conn: &Connection) -> Result<Vec<WordListMetada
     let res = query
         .query_map([], |row| {
             let create_time = SystemTime::UNIX_EPOCH
-                .checked_add(Duration::from_secs(row.get(3)?))
+                .checked_add(Duration::from_secs(row.get("create_time")?))
                 .expect("system time should not be out of bounds");
-            let min_occurrence_words = row.get(4)?;
-            let min_occurrence_unknown_chars = row.get(5)?;
             let analysis_query = AnalysisQuery {
-                min_occurrence_words,
-                min_occurrence_unknown_chars,
+                min_occurrence_words: row.get("min_occurrence_words")?,
+                min_occurrence_unknown_chars: row.get("min_occurrence_chars")?,
+                max_level: row.get("max_level")?,
+                exclude_guessable: row.get("exclude_guessable")?,
+                // filters that no longer parse are dropped
+                filter: row
+                    .get::<_, Option<String>>("filter")?
+                    .and_then(|filter| FilterExpr::parse(&filter).ok()),
             };
             Ok(WordListMetadata {
-                id: row.get(0)?,
-                book_name: row.get(1)?,
-                author_name: row.get(2)?,
+                id: row.get("id")?,
+                book_name: row.get("book_name")?,
+                author_name: row.get("author_name")?,
                 create_time,
                 analysis_query,
+                coverage_target: row.get("coverage_target")?,
+                character_focused: row.get("character_focused")?,
+                library_wide: row.get("library_wide")?,
             })
         })?
         .collect::<Result<Vec<WordListMetadata>, _>>()?;
@@ -93,7 +118,7 @@ pub fn db_wlist_select_by_id(
     let mut query = conn.prepare(SELECT_WORD_LIST_QUERY)?;
     let res = query
         .query_map([word_list_id], |row| {
-            let words_per_chapter_json: String = row.get(0)?;
+            let words_per_chapter_json: String = row.get("word_list_json")?;
             let words_per_chapter: Vec<ChapterWords> =
                 serde_json::from_str(&words_per_chapter_json)
                     .expect("failed to deserialize words per chapter lists");
@@ -108,3 +133,51 @@ pub fn db_wlist_delete_by_id(conn: &Connection, word_list_id: i64) -> Result<()>
     conn.execute(DELETE_WORD_LIST_QUERY, params![word_list_id])?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::AnalysisQuery;
+    use crate::db::open_test_db;
+    use crate::db::word_lists::{
+        db_wlist_insert, db_wlist_select_all_mdata, db_wlist_select_by_id,
+    };
+    use crate::filter::FilterExpr;
+    use crate::word_lists::{ChapterWords, WordList, WordListMetadata};
+
+    #[test]
+    fn metadata_round_trip() {
+        let conn = open_test_db();
+        let mut query = AnalysisQuery::new(2);
+        query.min_occurrence_unknown_chars = Some(4);
+        query.max_level = Some(3);
+        query.exclude_guessable = true;
+        query.filter = Some(FilterExpr::parse("len >= 2").unwrap());
+        let mut metadata = WordListMetadata::new("书", "作者", query.clone());
+        metadata.coverage_target = Some(95);
+        metadata.library_wide = true;
+        let chapters = vec![ChapterWords {
+            chapter_name: "一".to_string(),
+            tagged_words: vec![],
+        }];
+        db_wlist_insert(
+            &conn,
+            WordList {
+                metadata,
+                words_per_chapter: chapters,
+            },
+        )
+        .unwrap();
+
+        let all = db_wlist_select_all_mdata(&conn).unwrap();
+        assert_eq!(all.len(), 1);
+        let metadata = &all[0];
+        assert_eq!(metadata.book_name, "书");
+        assert_eq!(metadata.author_name, "作者");
+        assert_eq!(metadata.analysis_query, query);
+        assert_eq!(metadata.coverage_target, Some(95));
+        assert!(!metadata.character_focused);
+        assert!(metadata.library_wide);
+        let chapters = db_wlist_select_by_id(&conn, metadata.id).unwrap().unwrap();
+        assert_eq!(chapters[0].chapter_name, "一");
+    }
+}
@@ -52,6 +52,26 @@ pub fn open_as_flat_book(filename: &str) -> Result<FlatBook> {
     Ok(flat_book)
 }
 
+/// Open a plain text file as book with a single chapter, titled after the file name
+pub fn open_text_as_flat_book(filename: &str) -> Result<FlatBook> {
+    let path = PathBuf::from(filename);
+    let title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let content = fs::read_to_string(&path)?;
+    Ok(FlatBook {
+        title: title.clone(),
+        author: String::new(),
+        preface_content: String::new(),
+        chapters: vec![FlatChapter {
+            title,
+            content,
+            index: 0,
+        }],
+    })
+}
+
 fn open_epub_as_book(filepath: &Path) -> Result<Book> {
     let bytes = fs::read(filepath)?;
     Ok(epub_to_book(&bytes)?)
@@ -152,7 +172,7 @@ mod tests {
         let book = get_example_book();
         let flattened = flatten_book(&book, 1);
         assert_eq!(flattened.chapters.len(), 2);
-        let chapter1 = flattened.chapters.get(0).unwrap();
+        let chapter1 = flattened.chapters.first().unwrap();
         let chapter2 = flattened.chapters.get(1).unwrap();
         assert!(chapter1.content.contains("1.1 text"));
         assert!(chapter1.content.ends_with("1.1.1 text"));
@@ -164,7 +184,7 @@ mod tests {
         let book = get_example_book();
         let flattened = flatten_book(&book, 2);
         assert_eq!(flattened.chapters.len(), 3);
-        let chapter1 = flattened.chapters.get(0).unwrap();
+        let chapter1 = flattened.chapters.first().unwrap();
         let chapter2 = flattened.chapters.get(1).unwrap();
         assert!(chapter1.content.ends_with("1 text"));
         assert!(chapter2.content.ends_with("1.1.1 text"));
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::segmentation::BookSegmentation;
+
+/// Amount of example sentences that are kept per word
+pub const MAX_EXAMPLE_SENTENCES: usize = 3;
+
+/// Collect up to max_examples example sentences for each of the given words
+///
+/// sentences with fewer other unknown words are preferred, then shorter ones,
+/// chapters of books stored without sentences are skipped
+pub fn get_example_sentences(
+    book: &BookSegmentation,
+    words: &HashSet<&str>,
+    known_words: &HashSet<String>,
+    max_examples: usize,
+) -> HashMap<String, Vec<String>> {
+    // (other unknown words, sentence length, sentence) candidates per word
+    let mut candidates: HashMap<&str, Vec<(usize, usize, String)>> = HashMap::new();
+    for chapter in &book.chapter_cuts {
+        for sentence in chapter.sentence_cuts() {
+            let unknown_in_sentence: HashSet<&str> = sentence
+                .words
+                .iter()
+                .filter(|w| !known_words.contains(*w))
+                .map(|w| w.as_str())
+                .collect();
+            for word in unknown_in_sentence.iter().filter(|w| words.contains(*w)) {
+                let key = (unknown_in_sentence.len() - 1, sentence.text.chars().count());
+                let word_candidates = candidates.entry(word).or_default();
+                if word_candidates.iter().any(|(_, _, s)| s == sentence.text) {
+                    continue;
+                }
+                // only bother with sentences that make it into the current best
+                if word_candidates.len() == max_examples
+                    && word_candidates
+                        .last()
+                        .is_some_and(|(u, l, _)| (*u, *l) <= key)
+                {
+                    continue;
+                }
+                word_candidates.push((key.0, key.1, sentence.text.to_string()));
+                word_candidates.sort();
+                word_candidates.truncate(max_examples);
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .map(|(word, sentences)| {
+            (
+                word.to_string(),
+                sentences.into_iter().map(|(_, _, s)| s).collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::examples::get_example_sentences;
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation, Sentence};
+
+    #[test]
+    fn prefer_sentences_with_few_unknown_words() {
+        let cut: Vec<String> = [
+            "我", "看见", "猫", "我", "看见", "狗", "和", "猫", "猫", "很", "可爱",
+        ]
+        .iter()
+        .map(|w| w.to_string())
+        .collect();
+        let sentence = |text: &str, cut_end| Sentence {
+            text: text.to_string(),
+            cut_end,
+        };
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut,
+                sentences: vec![
+                    sentence("我看见猫。", 3),
+                    sentence("我看见狗和猫。", 8),
+                    sentence("猫很可爱。", 11),
+                ],
+            }],
+        };
+        let known: HashSet<String> = ["我", "看见", "和", "很", "可爱"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        let words = HashSet::from(["猫"]);
+        let examples = get_example_sentences(&book, &words, &known, 2);
+        assert_eq!(
+            examples.get("猫").unwrap(),
+            &vec!["我看见猫。".to_string(), "猫很可爱。".to_string()]
+        );
+    }
+
+    #[test]
+    fn skip_chapters_without_sentences() {
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: vec!["我".to_string(), "看见".to_string(), "猫".to_string()],
+                sentences: vec![],
+            }],
+        };
+        let words = HashSet::from(["猫"]);
+        let examples = get_example_sentences(&book, &words, &HashSet::new(), 2);
+        assert!(examples.is_empty());
+    }
+}
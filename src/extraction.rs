@@ -1,6 +1,8 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
+use crate::levels::WordLevel;
 use crate::segmentation::{segment_text, BookSegmentation, ChapterSegmentation};
 use jieba_rs::Jieba;
 use lazy_static::lazy_static;
@@ -8,11 +10,36 @@ use regex::Regex;
 
 use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(PartialEq, Eq, Hash)]
+#[derive(PartialEq, Eq, Hash, Clone)]
 pub struct ExtractionItem {
     pub word: String,
     pub frequency: u64,
     pub first_location: String,
+    /// Occurrences of the word per chapter, indexed like the book's chapters
+    pub chapter_frequencies: Vec<u64>,
+    /// Level and frequency rank according to imported lists
+    pub level: WordLevel,
+}
+
+impl ExtractionItem {
+    /// Occurrences of the word within the given range of chapters
+    pub fn frequency_in_chapters(&self, chapters: Range<usize>) -> u64 {
+        let end = chapters.end.min(self.chapter_frequencies.len());
+        let start = chapters.start.min(end);
+        self.chapter_frequencies[start..end].iter().sum()
+    }
+
+    pub fn chapters_containing(&self) -> usize {
+        self.chapter_frequencies.iter().filter(|f| **f > 0).count()
+    }
+
+    /// Share of chapters the word occurs in
+    pub fn dispersion(&self) -> f64 {
+        if self.chapter_frequencies.is_empty() {
+            return 0.0;
+        }
+        self.chapters_containing() as f64 / self.chapter_frequencies.len() as f64
+    }
 }
 
 pub struct ExtractionResult {
@@ -37,18 +64,33 @@ pub fn extract_vocab_from_segmented(segmented_book: BookSegmentation) -> Extract
     if segmented_book.chapter_cuts.is_empty() {
         panic!("expected book with at least one chapter!");
     }
-    let mut word_frequencies: HashMap<String, u64> = HashMap::new();
+    let chapter_amount = segmented_book.chapter_cuts.len();
+    let mut word_frequencies: HashMap<String, Vec<u64>> = HashMap::new();
     let mut word_occurrences: HashMap<String, String> = HashMap::new();
-    for ChapterSegmentation { title, cut } in segmented_book.chapter_cuts.clone() {
-        update_word_info(cut, title, &mut word_frequencies, &mut word_occurrences);
+    for (i, ChapterSegmentation { title, cut, .. }) in
+        segmented_book.chapter_cuts.iter().enumerate()
+    {
+        update_word_info(
+            cut,
+            i,
+            title,
+            chapter_amount,
+            &mut word_frequencies,
+            &mut word_occurrences,
+        );
     }
     let vocabulary = word_occurrences
         .into_iter()
         .filter(|(word, _)| contains_hanzi(word))
-        .map(|(word, chapter)| ExtractionItem {
-            word: word.to_string(),
-            frequency: *word_frequencies.get(&word).unwrap(),
-            first_location: chapter.to_string(),
+        .map(|(word, chapter)| {
+            let chapter_frequencies = word_frequencies.remove(&word).unwrap();
+            ExtractionItem {
+                word,
+                frequency: chapter_frequencies.iter().sum(),
+                first_location: chapter,
+                chapter_frequencies,
+                level: WordLevel::default(),
+            }
         })
         .collect();
     ExtractionResult {
@@ -69,19 +111,23 @@ pub fn word_to_hanzi(word: &str) -> Vec<&str> {
 }
 
 fn update_word_info(
-    words: Vec<String>,
-    chapter_title: String,
-    word_frequencies: &mut HashMap<String, u64>,
+    words: &[String],
+    chapter_index: usize,
+    chapter_title: &str,
+    chapter_amount: usize,
+    word_frequencies: &mut HashMap<String, Vec<u64>>,
     word_occurrences: &mut HashMap<String, String>,
 ) {
     for word in words {
         match word_frequencies.entry(word.clone()) {
             Entry::Occupied(o) => {
-                *o.into_mut() += 1;
+                o.into_mut()[chapter_index] += 1;
             }
             Entry::Vacant(v) => {
-                v.insert(1);
-                word_occurrences.insert(word, chapter_title.clone());
+                let mut chapter_frequencies = vec![0; chapter_amount];
+                chapter_frequencies[chapter_index] = 1;
+                v.insert(chapter_frequencies);
+                word_occurrences.insert(word.clone(), chapter_title.to_string());
             }
         }
     }
@@ -89,7 +135,8 @@ fn update_word_info(
 
 #[cfg(test)]
 mod tests {
-    use crate::extraction::contains_hanzi;
+    use crate::extraction::{contains_hanzi, extract_vocab_from_segmented};
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
 
     #[test]
     fn match_hanzi_words() {
@@ -104,4 +151,28 @@ mod tests {
         assert!(!contains_hanzi(english));
         assert!(!contains_hanzi(punctuation));
     }
+
+    #[test]
+    fn record_chapter_frequencies() {
+        let chapter = |title: &str, cut: &[&str]| ChapterSegmentation {
+            title: title.to_string(),
+            cut: cut.iter().map(|w| w.to_string()).collect(),
+            sentences: vec![],
+        };
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![
+                chapter("一", &["你好", "世界"]),
+                chapter("二", &["世界", "世界"]),
+                chapter("三", &["你好"]),
+            ],
+        };
+        let res = extract_vocab_from_segmented(book);
+        let world = res.vocabulary.iter().find(|i| i.word == "世界").unwrap();
+        assert_eq!(world.frequency, 3);
+        assert_eq!(world.first_location, "一");
+        assert_eq!(world.chapter_frequencies, vec![1, 2, 0]);
+        assert_eq!(world.frequency_in_chapters(1..5), 2);
+        assert_eq!(world.chapters_containing(), 2);
+    }
 }
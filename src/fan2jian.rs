@@ -16,7 +16,7 @@ pub fn map_text(input_text: &str, fan2jian: bool) -> String {
         .collect()
 }
 
-fn map_word(word: &str, mapping: &HashMap<String, String>) -> String {
+pub fn map_word(word: &str, mapping: &HashMap<String, String>) -> String {
     match mapping.get(word) {
         // if whole word is in dict, return mapped entry
         Some(mapped) => mapped.to_string(),
@@ -0,0 +1,357 @@
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+use anyhow::{anyhow, Result};
+use jieba_rs::Jieba;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::extraction::{word_to_hanzi, ExtractionItem};
+
+pub const STOPWORDS_TEXT: &str = include_str!("../stopwords.txt");
+
+lazy_static! {
+    static ref STOPWORDS: HashSet<&'static str> = STOPWORDS_TEXT
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    // only loaded when a filter asks for proper nouns
+    static ref JIEBA: Jieba = Jieba::new();
+}
+
+/// A parsed filter expression over extracted words, e.g
+/// `freq <= 10 and len >= 2 and not (proper or stopword) and chapter 1..5 and word ~ "^大"`
+///
+/// fields: freq (occurrences in book), len (characters), level (e.g HSK), rank (frequency list)
+/// conditions: `chapter a..b` (occurs in chapters a to b), proper (proper noun),
+/// stopword, `word ~ "regex"`; combined with and/or/not and parentheses
+#[derive(Debug, Clone)]
+pub struct FilterExpr {
+    source: String,
+    expr: Expr,
+}
+
+impl FilterExpr {
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(anyhow!("unexpected {:?} in filter", token));
+        }
+        Ok(Self {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, item: &ExtractionItem) -> bool {
+        self.expr.eval(item)
+    }
+}
+
+// filters are identified by their source, so that queries containing them can be memoized
+impl PartialEq for FilterExpr {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for FilterExpr {}
+
+impl Hash for FilterExpr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Frequency,
+    Length,
+    Level,
+    Rank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn compare(self, a: u64, b: u64) -> bool {
+        match self {
+            Comparison::Less => a < b,
+            Comparison::LessEqual => a <= b,
+            Comparison::Greater => a > b,
+            Comparison::GreaterEqual => a >= b,
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Comparison, u64),
+    /// 1-based, inclusive chapter range
+    Chapters(usize, usize),
+    ProperNoun,
+    Stopword,
+    Matches(Regex),
+}
+
+impl Expr {
+    fn eval(&self, item: &ExtractionItem) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(item) && b.eval(item),
+            Expr::Or(a, b) => a.eval(item) || b.eval(item),
+            Expr::Not(a) => !a.eval(item),
+            Expr::Compare(field, comparison, value) => {
+                let actual = match field {
+                    Field::Frequency => Some(item.frequency),
+                    Field::Length => Some(word_to_hanzi(&item.word).len() as u64),
+                    Field::Level => item.level.level.map(u64::from),
+                    Field::Rank => item.level.rank,
+                };
+                // words without level or rank never fulfill a comparison on them
+                actual.is_some_and(|actual| comparison.compare(actual, *value))
+            }
+            Expr::Chapters(from, to) => item.frequency_in_chapters(from.saturating_sub(1)..*to) > 0,
+            Expr::ProperNoun => is_proper_noun(&item.word),
+            Expr::Stopword => STOPWORDS.contains(item.word.as_str()),
+            Expr::Matches(regex) => regex.is_match(&item.word),
+        }
+    }
+}
+
+fn is_proper_noun(word: &str) -> bool {
+    match JIEBA.tag(word, false).as_slice() {
+        [tag] => ["nr", "ns", "nt", "nz"]
+            .iter()
+            .any(|proper| tag.tag.starts_with(proper)),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u64),
+    Str(String),
+    Op(&'static str),
+    OpenParen,
+    CloseParen,
+}
+
+const OPERATORS: [&str; 9] = ["<=", ">=", "!=", "..", "==", "<", ">", "=", "~"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '(' || c == ')' {
+            tokens.push(if c == '(' {
+                Token::OpenParen
+            } else {
+                Token::CloseParen
+            });
+            rest = &rest[1..];
+        } else if c == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| anyhow!("unterminated string in filter"))?;
+            tokens.push(Token::Str(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Number(rest[..end].parse()?));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_lowercase()));
+            rest = &rest[end..];
+        } else {
+            return Err(anyhow!("unexpected character {} in filter", c));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("unexpected end of filter"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        match self.next()? {
+            Token::OpenParen => {
+                let expr = self.parse_or()?;
+                match self.next()? {
+                    Token::CloseParen => Ok(expr),
+                    token => Err(anyhow!("expected ) but got {:?}", token)),
+                }
+            }
+            Token::Ident(ident) => self.parse_condition(&ident),
+            token => Err(anyhow!("expected condition but got {:?}", token)),
+        }
+    }
+
+    fn parse_condition(&mut self, ident: &str) -> Result<Expr> {
+        let field = match ident {
+            "proper" => return Ok(Expr::ProperNoun),
+            "stopword" => return Ok(Expr::Stopword),
+            "chapter" | "chapters" => {
+                let from = self.parse_number()?;
+                let to = match self.peek() {
+                    Some(Token::Op("..")) => {
+                        self.pos += 1;
+                        self.parse_number()?
+                    }
+                    _ => from,
+                };
+                if from == 0 || to < from {
+                    return Err(anyhow!("invalid chapter range {}..{}", from, to));
+                }
+                return Ok(Expr::Chapters(from as usize, to as usize));
+            }
+            "word" => {
+                match self.next()? {
+                    Token::Op("~") => {}
+                    token => return Err(anyhow!("expected ~ after word but got {:?}", token)),
+                }
+                return match self.next()? {
+                    Token::Str(pattern) => Ok(Expr::Matches(Regex::new(&pattern)?)),
+                    token => Err(anyhow!("expected quoted regex but got {:?}", token)),
+                };
+            }
+            "freq" | "frequency" => Field::Frequency,
+            "len" | "length" => Field::Length,
+            "level" | "hsk" => Field::Level,
+            "rank" => Field::Rank,
+            _ => return Err(anyhow!("unknown filter condition {}", ident)),
+        };
+        let comparison = match self.next()? {
+            Token::Op("<") => Comparison::Less,
+            Token::Op("<=") => Comparison::LessEqual,
+            Token::Op(">") => Comparison::Greater,
+            Token::Op(">=") => Comparison::GreaterEqual,
+            Token::Op("=") | Token::Op("==") => Comparison::Equal,
+            Token::Op("!=") => Comparison::NotEqual,
+            token => return Err(anyhow!("expected comparison but got {:?}", token)),
+        };
+        Ok(Expr::Compare(field, comparison, self.parse_number()?))
+    }
+
+    fn parse_number(&mut self) -> Result<u64> {
+        match self.next()? {
+            Token::Number(n) => Ok(n),
+            token => Err(anyhow!("expected number but got {:?}", token)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::extraction::ExtractionItem;
+    use crate::filter::FilterExpr;
+    use crate::levels::WordLevel;
+
+    fn item(word: &str, chapter_frequencies: Vec<u64>, level: Option<u8>) -> ExtractionItem {
+        ExtractionItem {
+            word: word.to_string(),
+            frequency: chapter_frequencies.iter().sum(),
+            first_location: "一".to_string(),
+            chapter_frequencies,
+            level: WordLevel { level, rank: None },
+        }
+    }
+
+    #[test]
+    fn evaluate_filter_expression() {
+        let filter =
+            FilterExpr::parse("freq <= 3 and len >= 2 and (level <= 4 or chapter 2..3)").unwrap();
+        assert!(filter.matches(&item("学习", vec![1, 1, 0], Some(1))));
+        assert!(filter.matches(&item("细胞", vec![0, 2, 0], None)));
+        assert!(!filter.matches(&item("细胞", vec![2, 0, 0], None)));
+        assert!(!filter.matches(&item("学习", vec![4, 0, 0], Some(1))));
+        assert!(!filter.matches(&item("学", vec![1, 0, 0], Some(1))));
+        let filter = FilterExpr::parse("not stopword and word ~ \"^大\"").unwrap();
+        assert!(filter.matches(&item("大学", vec![1], None)));
+        assert!(!filter.matches(&item("学习", vec![1], None)));
+    }
+
+    #[test]
+    fn reject_malformed_filters() {
+        assert!(FilterExpr::parse("freq <=").is_err());
+        assert!(FilterExpr::parse("size > 2").is_err());
+        assert!(FilterExpr::parse("(len > 2").is_err());
+        assert!(FilterExpr::parse("word ~ \"[\"").is_err());
+        assert!(FilterExpr::parse("chapter 3..1").is_err());
+        assert!(FilterExpr::parse("len > 2 len < 4").is_err());
+    }
+}
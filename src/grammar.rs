@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+
+use crate::segmentation::BookSegmentation;
+
+pub const GRAMMAR_PATTERNS_TEXT: &str = include_str!("../grammar_patterns.txt");
+
+/// Maximum amount of example sentences that are kept per grammar pattern
+pub const MAX_GRAMMAR_EXAMPLES: usize = 10;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum PatternElement {
+    /// one of the given words
+    Words(Vec<String>),
+    /// any single word
+    AnyWord,
+    /// any amount of words, including none
+    Gap,
+}
+
+impl PatternElement {
+    fn parse(s: &str) -> Self {
+        match s {
+            "*" => Self::AnyWord,
+            "..." | "…" => Self::Gap,
+            _ => Self::Words(s.split('|').map(|w| w.to_string()).collect()),
+        }
+    }
+}
+
+/// A discontinuous pattern of words, such as 越 ... 越
+#[derive(Debug, Clone)]
+pub struct GrammarPattern {
+    pub name: String,
+    elements: Vec<PatternElement>,
+}
+
+impl GrammarPattern {
+    pub fn parse(name: &str, pattern: &str) -> Result<Self> {
+        let elements: Vec<PatternElement> = pattern
+            .split_whitespace()
+            .map(PatternElement::parse)
+            .collect();
+        if !elements
+            .iter()
+            .any(|e| matches!(e, PatternElement::Words(_)))
+        {
+            return Err(anyhow!(
+                "pattern {} must contain at least one literal word",
+                name
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            elements,
+        })
+    }
+
+    /// Count non-overlapping matches within the words of a sentence
+    pub fn count_matches(&self, words: &[String]) -> u64 {
+        let mut count = 0;
+        let mut start = 0;
+        while start < words.len() {
+            match match_elements(&self.elements, &words[start..]) {
+                Some(len) if len > 0 => {
+                    count += 1;
+                    start += len;
+                }
+                _ => start += 1,
+            }
+        }
+        count
+    }
+}
+
+/// Occurrences of a grammar pattern within a book
+pub struct GrammarOccurrences {
+    pub pattern_name: String,
+    pub chapter_counts: Vec<u64>,
+    pub examples: Vec<String>,
+}
+
+impl GrammarOccurrences {
+    pub fn total(&self) -> u64 {
+        self.chapter_counts.iter().sum()
+    }
+
+    pub fn chapters_containing(&self) -> usize {
+        self.chapter_counts.iter().filter(|c| **c > 0).count()
+    }
+
+    /// Titles and occurrence counts of the chapters the pattern occurs in
+    pub fn chapter_breakdown<'a>(&self, chapter_titles: &'a [String]) -> Vec<(&'a str, u64)> {
+        chapter_titles
+            .iter()
+            .zip(&self.chapter_counts)
+            .filter(|(_, count)| **count > 0)
+            .map(|(title, count)| (title.as_str(), *count))
+            .collect()
+    }
+}
+
+/// Parse patterns from text, one `<name>,<pattern>` per line, lines starting with # are ignored
+pub fn parse_grammar_patterns(text: &str) -> Result<Vec<GrammarPattern>> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once(',') {
+            Some((name, pattern)) => GrammarPattern::parse(name.trim(), pattern),
+            None => Err(anyhow!("malformatted grammar pattern line: {}", line)),
+        })
+        .collect()
+}
+
+pub fn get_grammar_patterns() -> Vec<GrammarPattern> {
+    parse_grammar_patterns(GRAMMAR_PATTERNS_TEXT).expect("bundled grammar patterns are invalid")
+}
+
+/// Count occurrences of all patterns per chapter and collect example sentences
+///
+/// patterns that do not occur at all are omitted, result is sorted by total occurrences
+pub fn analyze_grammar(
+    book: &BookSegmentation,
+    patterns: &[GrammarPattern],
+) -> Vec<GrammarOccurrences> {
+    let mut occurrences: Vec<GrammarOccurrences> = patterns
+        .iter()
+        .map(|pattern| GrammarOccurrences {
+            pattern_name: pattern.name.clone(),
+            chapter_counts: vec![0; book.chapter_cuts.len()],
+            examples: vec![],
+        })
+        .collect();
+    for (i, chapter) in book.chapter_cuts.iter().enumerate() {
+        // chapters segmented before sentences were kept are only counted, they have no examples
+        if chapter.sentences.is_empty() {
+            for (pattern, occ) in patterns.iter().zip(occurrences.iter_mut()) {
+                occ.chapter_counts[i] += pattern.count_matches(&chapter.cut);
+            }
+            continue;
+        }
+        for sentence in chapter.sentence_cuts() {
+            for (pattern, occ) in patterns.iter().zip(occurrences.iter_mut()) {
+                let count = pattern.count_matches(sentence.words);
+                if count > 0 {
+                    occ.chapter_counts[i] += count;
+                    if occ.examples.len() < MAX_GRAMMAR_EXAMPLES {
+                        occ.examples.push(sentence.text.to_string());
+                    }
+                }
+            }
+        }
+    }
+    occurrences.retain(|occ| occ.total() > 0);
+    occurrences.sort_by_key(|occ| std::cmp::Reverse(occ.total()));
+    occurrences
+}
+
+/// Plain text listing of grammar pattern occurrences per chapter and their example sentences
+pub fn grammar_report(chapter_titles: &[String], occurrences: &[GrammarOccurrences]) -> String {
+    let mut report = String::new();
+    for occ in occurrences {
+        report.push_str(&format!(
+            "{} ({} occurrences in {} chapters)\n",
+            occ.pattern_name,
+            occ.total(),
+            occ.chapters_containing()
+        ));
+        for (title, count) in occ.chapter_breakdown(chapter_titles) {
+            report.push_str(&format!("  {}: {}\n", title, count));
+        }
+        for example in &occ.examples {
+            report.push_str(&format!("    {}\n", example));
+        }
+        report.push('\n');
+    }
+    report
+}
+
+// match elements at start of words (lazily), return amount of words matched
+fn match_elements(elements: &[PatternElement], words: &[String]) -> Option<usize> {
+    match elements.split_first() {
+        None => Some(0),
+        Some((PatternElement::Gap, rest)) => (0..=words.len())
+            .find_map(|skip| match_elements(rest, &words[skip..]).map(|len| skip + len)),
+        Some((element, rest)) => {
+            let word = words.first()?;
+            let matches = match element {
+                PatternElement::Words(alternatives) => alternatives.contains(word),
+                _ => true,
+            };
+            if matches {
+                match_elements(rest, &words[1..]).map(|len| len + 1)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grammar::*;
+    use crate::segmentation::ChapterSegmentation;
+
+    fn words(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn bundled_patterns_parse() {
+        assert!(!get_grammar_patterns().is_empty());
+    }
+
+    #[test]
+    fn match_discontinuous_pattern() {
+        let pattern = GrammarPattern::parse("越…越", "越 ... 越").unwrap();
+        assert_eq!(pattern.count_matches(&words("他 越 跑 越 快")), 1);
+        assert_eq!(pattern.count_matches(&words("越 越")), 1);
+        assert_eq!(pattern.count_matches(&words("他 越 跑")), 0);
+    }
+
+    #[test]
+    fn match_alternatives_and_any_word() {
+        let pattern = GrammarPattern::parse("连…都/也", "连 ... 都|也").unwrap();
+        assert_eq!(pattern.count_matches(&words("连 他 也 不 知道")), 1);
+        let pattern = GrammarPattern::parse("把", "把 *").unwrap();
+        assert_eq!(pattern.count_matches(&words("把 门 关 上 把 窗")), 2);
+        assert_eq!(pattern.count_matches(&words("关 上 把")), 0);
+    }
+
+    #[test]
+    fn count_chapters_without_sentences_without_examples() {
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: words("他 越 跑 越 快 我 越 看 越 喜欢"),
+                sentences: vec![],
+            }],
+        };
+        let pattern = GrammarPattern::parse("越…越", "越 ... 越").unwrap();
+        let occurrences = analyze_grammar(&book, &[pattern]);
+        assert_eq!(occurrences[0].chapter_counts, vec![2]);
+        assert!(occurrences[0].examples.is_empty());
+    }
+
+    #[test]
+    fn report_occurrences_per_chapter() {
+        let occurrences = vec![GrammarOccurrences {
+            pattern_name: "把".to_string(),
+            chapter_counts: vec![2, 0, 1],
+            examples: vec!["把门关上。".to_string()],
+        }];
+        let titles = vec!["一".to_string(), "二".to_string(), "三".to_string()];
+        assert_eq!(
+            occurrences[0].chapter_breakdown(&titles),
+            vec![("一", 2), ("三", 1)]
+        );
+        assert_eq!(
+            grammar_report(&titles, &occurrences),
+            "把 (3 occurrences in 2 chapters)\n  一: 2\n  三: 1\n    把门关上。\n\n"
+        );
+    }
+
+    #[test]
+    fn reject_pattern_without_words() {
+        assert!(GrammarPattern::parse("gap", "* ...").is_err());
+        assert!(parse_grammar_patterns("no comma here").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::{
+    config::get_config,
+    db::{
+        history::db_history_insert,
+        vocab::{db_words_select_all, describe_known_levels, VocabStatus},
+    },
+    vocabulary::{get_vocab_stats, VocabularyInfo},
+};
+
+pub const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+pub const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+pub const MONTH: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Vocabulary counts at a point in time
+#[derive(Clone)]
+pub struct VocabSnapshot {
+    pub time: SystemTime,
+    /// counts of known words and chars, as per the known levels at the time
+    pub info: VocabularyInfo,
+    /// none for snapshots taken before counts per status were kept
+    pub status_counts: Option<StatusCounts>,
+    /// known levels in effect when the snapshot was taken, e.g young,mature
+    pub known_levels: Option<String>,
+}
+
+/// Amount of words per status, independent of which levels count as known
+///
+/// words in Anki are counted with their Anki status, only the other added external words as external
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusCounts {
+    pub suspended: usize,
+    pub new: usize,
+    pub learning: usize,
+    pub young: usize,
+    pub mature: usize,
+    pub external: usize,
+}
+
+impl StatusCounts {
+    pub fn count(statuses: impl Iterator<Item = VocabStatus>) -> Self {
+        let mut counts = StatusCounts::default();
+        for status in statuses {
+            match status {
+                VocabStatus::Suspended => counts.suspended += 1,
+                VocabStatus::New => counts.new += 1,
+                VocabStatus::Learning => counts.learning += 1,
+                VocabStatus::Young => counts.young += 1,
+                VocabStatus::Mature => counts.mature += 1,
+                VocabStatus::AddedExternal => counts.external += 1,
+            }
+        }
+        counts
+    }
+
+    fn values(&self) -> [usize; 6] {
+        [
+            self.suspended,
+            self.new,
+            self.learning,
+            self.young,
+            self.mature,
+            self.external,
+        ]
+    }
+}
+
+/// Change of known words and chars within a period
+#[derive(Debug, PartialEq, Eq)]
+pub struct Growth {
+    pub end: SystemTime,
+    pub words: i64,
+    pub chars: i64,
+}
+
+/// Record current vocabulary counts, to be called after every change of the vocabulary
+pub fn record_vocab_snapshot(conn: &Connection) -> Result<VocabularyInfo> {
+    let info = get_vocab_stats(conn)?;
+    // with all Anki levels known, Anki words keep their status even if also added external
+    let statuses = db_words_select_all(conn, &VocabStatus::ANKI_LEVELS)?;
+    let status_counts = StatusCounts::count(statuses.into_values());
+    let known_levels = describe_known_levels(&get_config().anki_levels.known);
+    db_history_insert(conn, &info, &status_counts, &known_levels)?;
+    Ok(info)
+}
+
+// latest snapshot at time, the first one if time is before all snapshots
+fn info_at(history: &[VocabSnapshot], time: SystemTime) -> Option<&VocabularyInfo> {
+    history
+        .iter()
+        .take_while(|snapshot| snapshot.time <= time)
+        .last()
+        .or(history.first())
+        .map(|snapshot| &snapshot.info)
+}
+
+/// Growth within the last `periods` periods of given length before now, oldest first
+pub fn get_growth(
+    history: &[VocabSnapshot],
+    period: Duration,
+    periods: usize,
+    now: SystemTime,
+) -> Vec<Growth> {
+    (0..periods)
+        .rev()
+        .map(|i| {
+            let end = now - period * i as u32;
+            let start = end - period;
+            let (words, chars) = match (info_at(history, start), info_at(history, end)) {
+                (Some(start), Some(end)) => (
+                    end.words_known as i64 - start.words_known as i64,
+                    end.chars_known as i64 - start.chars_known as i64,
+                ),
+                _ => (0, 0),
+            };
+            Growth { end, words, chars }
+        })
+        .collect()
+}
+
+/// Known words at the end of each of the last `days` days, oldest first
+pub fn get_daily_known_words(history: &[VocabSnapshot], days: usize, now: SystemTime) -> Vec<u64> {
+    (0..days)
+        .rev()
+        .map(|i| {
+            let time = now - DAY * i as u32;
+            // days before the first snapshot are empty
+            match history.first() {
+                Some(first) if first.time <= time => {
+                    info_at(history, time).map_or(0, |info| info.words_known as u64)
+                }
+                _ => 0,
+            }
+        })
+        .collect()
+}
+
+/// CSV of all snapshots, counts per status and known levels are empty for snapshots taken before they were kept
+pub fn history_csv(history: &[VocabSnapshot]) -> String {
+    let mut csv = String::from(
+        "date,time,words_known,words_active,words_inactive,chars_known,chars_active,chars_inactive,\
+         words_suspended,words_new,words_learning,words_young,words_mature,words_external,known_levels\n",
+    );
+    for snapshot in history {
+        let info = &snapshot.info;
+        let status_counts = match &snapshot.status_counts {
+            Some(counts) => counts
+                .values()
+                .iter()
+                .map(|count| count.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+            None => ",,,,,".to_string(),
+        };
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},\"{}\"",
+            format_date(snapshot.time),
+            unix_secs(snapshot.time),
+            info.words_known,
+            info.words_active,
+            info.words_inactive,
+            info.chars_known,
+            info.chars_active,
+            info.chars_inactive,
+            status_counts,
+            snapshot.known_levels.as_deref().unwrap_or_default()
+        );
+    }
+    csv
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// UTC date as YYYY-MM-DD
+pub fn format_date(time: SystemTime) -> String {
+    // civil date from days since epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (unix_secs(time) / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::history::*;
+    use crate::vocabulary::VocabularyInfo;
+
+    fn snapshot(day: u32, words_known: usize) -> VocabSnapshot {
+        VocabSnapshot {
+            time: SystemTime::UNIX_EPOCH + DAY * day,
+            info: VocabularyInfo {
+                words_known,
+                words_active: words_known,
+                words_inactive: 0,
+                chars_known: words_known / 2,
+                chars_active: words_known / 2,
+                chars_inactive: 0,
+            },
+            status_counts: None,
+            known_levels: None,
+        }
+    }
+
+    #[test]
+    fn format_unix_time_as_date() {
+        assert_eq!(format_date(SystemTime::UNIX_EPOCH), "1970-01-01");
+        let leap_day = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_208_000);
+        assert_eq!(format_date(leap_day), "2024-02-29");
+    }
+
+    #[test]
+    fn growth_per_period() {
+        let history = vec![snapshot(10, 100), snapshot(12, 110), snapshot(20, 150)];
+        let now = SystemTime::UNIX_EPOCH + DAY * 21;
+        let words: Vec<i64> = get_growth(&history, WEEK, 3, now)
+            .iter()
+            .map(|growth| growth.words)
+            .collect();
+        // weeks ending on day 7, 14 and 21
+        assert_eq!(words, vec![0, 10, 40]);
+        let daily = get_daily_known_words(&history, 4, SystemTime::UNIX_EPOCH + DAY * 12);
+        assert_eq!(daily, vec![0, 100, 100, 110]);
+        assert!(history_csv(&history).contains("1970-01-13,1036800,110,110,0,55,55,0,,,,,,,\"\"\n"));
+        let mut recent = snapshot(21, 160);
+        recent.status_counts = Some(StatusCounts::count(
+            [VocabStatus::Young, VocabStatus::Mature, VocabStatus::Mature].into_iter(),
+        ));
+        recent.known_levels = Some("young,mature".to_string());
+        assert!(history_csv(&[recent])
+            .contains("1970-01-22,1814400,160,160,0,80,80,0,0,0,0,1,2,0,\"young,mature\"\n"));
+    }
+}
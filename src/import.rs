@@ -0,0 +1,168 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    extraction::contains_hanzi,
+    fan2jian::{get_mapping, map_word},
+    levels::{split_alternatives, split_fields},
+};
+
+/// Format of a vocabulary file to add words from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VocabFormat {
+    /// one word per line
+    Plain,
+    /// Pleco flashcard export, tab separated headword, pinyin and definition
+    Pleco,
+    /// Skritter CSV export with the word as first column
+    Skritter,
+    /// HSK list with the word as first Chinese field, e.g 1,爱,ài
+    Hsk,
+}
+
+impl FromStr for VocabFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "plain" | "txt" => Ok(VocabFormat::Plain),
+            "pleco" => Ok(VocabFormat::Pleco),
+            "skritter" => Ok(VocabFormat::Skritter),
+            "hsk" => Ok(VocabFormat::Hsk),
+            _ => Err(anyhow!(
+                "unknown vocabulary format {}, use plain, pleco, skritter or hsk",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for VocabFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            VocabFormat::Plain => "plain",
+            VocabFormat::Pleco => "pleco",
+            VocabFormat::Skritter => "skritter",
+            VocabFormat::Hsk => "hsk",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parse the words of a vocabulary file
+///
+/// headwords in traditional[simplified] or simplified[traditional] notation are added as simplified,
+/// lines without Chinese headword (headers, Pleco categories) are skipped
+pub fn parse_vocab_file(text: &str, format: VocabFormat) -> HashSet<String> {
+    let fan2jian = get_mapping(true);
+    let mut words = HashSet::new();
+    for line in text.lines() {
+        let line = line.trim();
+        match format {
+            VocabFormat::Plain => {
+                if !line.is_empty() {
+                    words.insert(line.to_string());
+                }
+            }
+            VocabFormat::Pleco => {
+                // category lines look like // Lesson 1
+                if line.starts_with("//") {
+                    continue;
+                }
+                if let Some(field) = line.split('\t').next() {
+                    words.insert(headword(field, &fan2jian));
+                }
+            }
+            VocabFormat::Skritter => {
+                if let Some(field) = line.split(',').next() {
+                    words.insert(headword(field.trim().trim_matches('"'), &fan2jian));
+                }
+            }
+            VocabFormat::Hsk => {
+                if let Some(field) = split_fields(line).into_iter().find(|f| contains_hanzi(f)) {
+                    let headword = headword(field, &fan2jian);
+                    words.extend(split_alternatives(&headword).map(|w| w.to_string()));
+                }
+            }
+        }
+    }
+    if format != VocabFormat::Plain {
+        words.retain(|word| contains_hanzi(word));
+    }
+    words
+}
+
+// 傳統[传统] and 传统[傳統] -> 传统, taking the side that is simplified already,
+// other headwords are kept as they are
+fn headword(field: &str, fan2jian: &HashMap<String, String>) -> String {
+    let field = field.trim();
+    let (first, second) = match field
+        .strip_suffix(']')
+        .and_then(|rest| rest.split_once('['))
+    {
+        Some((first, second)) if !second.trim().is_empty() => (first.trim(), second.trim()),
+        Some((first, _)) => return first.trim().to_string(),
+        None => return field.to_string(),
+    };
+    if map_word(first, fan2jian) == first {
+        first.to_string()
+    } else {
+        map_word(second, fan2jian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::import::*;
+
+    fn sorted(words: HashSet<String>) -> Vec<String> {
+        let mut words: Vec<String> = words.into_iter().collect();
+        words.sort();
+        words
+    }
+
+    #[test]
+    fn parse_pleco_export() {
+        let text = "// Lesson 1\n\
+                    傳統[传统]\tchuan2tong3\ttradition\n\
+                    猫\tmao1\tcat\n\
+                    \n";
+        assert_eq!(
+            sorted(parse_vocab_file(text, VocabFormat::Pleco)),
+            vec!["传统", "猫"]
+        );
+    }
+
+    #[test]
+    fn take_simplified_side_of_headword() {
+        let text = "传统[傳統]\tchuan2tong3\ttradition\n電話[电话]\tdian4hua4\ttelephone\n";
+        assert_eq!(
+            sorted(parse_vocab_file(text, VocabFormat::Pleco)),
+            vec!["传统", "电话"]
+        );
+        let text = "\"简单[簡單]\",jiǎndān,simple\n";
+        assert_eq!(
+            sorted(parse_vocab_file(text, VocabFormat::Skritter)),
+            vec!["简单"]
+        );
+    }
+
+    #[test]
+    fn parse_skritter_and_hsk_lists() {
+        let text = "Word,Reading,Definition\n\"電話[电话]\",diànhuà,telephone\n狗,gǒu,dog\n";
+        assert_eq!(
+            sorted(parse_vocab_file(text, VocabFormat::Skritter)),
+            vec!["狗", "电话"]
+        );
+        let text = "No,Simplified,Pinyin\n1,爱,ài\n2,爸爸｜爸,bàba\n";
+        assert_eq!(
+            sorted(parse_vocab_file(text, VocabFormat::Hsk)),
+            vec!["爱", "爸", "爸爸"]
+        );
+    }
+}
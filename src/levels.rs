@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::extraction::{contains_hanzi, ExtractionResult};
+
+/// Highest level of supported level lists (HSK 3.0 goes up to 7-9)
+pub const MAX_LEVEL: u8 = 9;
+
+/// Level (e.g HSK) and general frequency rank of a word
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct WordLevel {
+    pub level: Option<u8>,
+    pub rank: Option<u64>,
+}
+
+impl WordLevel {
+    pub fn description(&self) -> String {
+        match (self.level, self.rank) {
+            (Some(level), Some(rank)) => format!("HSK {} | #{}", level, rank),
+            (Some(level), None) => format!("HSK {}", level),
+            (None, Some(rank)) => format!("#{}", rank),
+            (None, None) => "".to_string(),
+        }
+    }
+}
+
+/// Parse a word level list
+///
+/// if level is given, the first field of every line is a word of that level
+/// (e.g one file per HSK level), otherwise lines must consist of a word and its level.
+/// Fields can be separated by tabs, commas or spaces, alternative forms of a word
+/// (e.g 爸爸｜爸) are all assigned the level
+pub fn parse_level_list(text: &str, level: Option<u8>) -> Result<HashMap<String, u8>> {
+    let mut levels = HashMap::new();
+    for line in text.lines() {
+        let fields = split_fields(line);
+        let word_field = match fields.first() {
+            Some(field) if contains_hanzi(field) => *field,
+            // headers and empty lines
+            _ => continue,
+        };
+        let word_level = match level {
+            Some(level) => level,
+            None => parse_level(&fields[1..].join(" "))
+                .ok_or_else(|| anyhow!("no level for {} in line: {}", word_field, line))?,
+        };
+        for word in split_alternatives(word_field) {
+            levels.insert(word.to_string(), word_level);
+        }
+    }
+    Ok(levels)
+}
+
+/// Parse a frequency list (e.g SUBTLEX-CH) into ranks, most frequent word has rank 1
+///
+/// if the second field of lines is a count, words are ranked by it,
+/// otherwise by their order within the file
+pub fn parse_frequency_list(text: &str) -> HashMap<String, u64> {
+    let mut counted_words: Vec<(usize, &str, Option<u64>)> = text
+        .lines()
+        .map(split_fields)
+        .filter(|fields| fields.first().is_some_and(|w| contains_hanzi(w)))
+        .enumerate()
+        .map(|(i, fields)| (i, fields[0], fields.get(1).and_then(|c| c.parse().ok())))
+        .collect();
+    if counted_words.iter().all(|(_, _, count)| count.is_some()) {
+        counted_words.sort_by(|(i1, _, c1), (i2, _, c2)| c2.cmp(c1).then(i1.cmp(i2)));
+    }
+    let mut ranks = HashMap::new();
+    for (rank, (_, word, _)) in counted_words.into_iter().enumerate() {
+        // keep the best rank if a word occurs multiple times
+        ranks.entry(word.to_string()).or_insert(rank as u64 + 1);
+    }
+    ranks
+}
+
+/// Set level and rank of all extracted words
+pub fn annotate_levels(extraction_res: &mut ExtractionResult, levels: &HashMap<String, WordLevel>) {
+    extraction_res.vocabulary = std::mem::take(&mut extraction_res.vocabulary)
+        .into_iter()
+        .map(|mut item| {
+            if let Some(level) = levels.get(&item.word) {
+                item.level = *level;
+            }
+            item
+        })
+        .collect();
+}
+
+pub(crate) fn split_fields(line: &str) -> Vec<&str> {
+    line.split(['\t', ',', ' '])
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+pub(crate) fn split_alternatives(word: &str) -> impl Iterator<Item = &str> {
+    word.split(['|', '｜', '/'])
+        .map(|w| w.trim())
+        .filter(|w| !w.is_empty())
+}
+
+// accepts plain numbers as well as e.g HSK3, HSK 7-9 (lowest level of band is used)
+fn parse_level(field: &str) -> Option<u8> {
+    let digits: String = field
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits
+        .parse()
+        .ok()
+        .filter(|level| (1..=MAX_LEVEL).contains(level))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::levels::*;
+
+    #[test]
+    fn parse_levels_with_and_without_given_level() {
+        let levels = parse_level_list("爱\n爸爸｜爸\n\n", Some(1)).unwrap();
+        assert_eq!(levels.get("爱"), Some(&1));
+        assert_eq!(levels.get("爸"), Some(&1));
+        let levels =
+            parse_level_list("word\tlevel\n学习\t1\n研究,HSK4\n细胞 HSK 7-9\n", None).unwrap();
+        assert_eq!(levels.get("学习"), Some(&1));
+        assert_eq!(levels.get("研究"), Some(&4));
+        assert_eq!(levels.get("细胞"), Some(&7));
+        assert!(parse_level_list("学习\n", None).is_err());
+    }
+
+    #[test]
+    fn rank_frequency_list_by_count() {
+        let text = "Total word count: 3\nWord\tWCount\n我\t50\n的\t100\n你\t20\n";
+        let ranks = parse_frequency_list(text);
+        assert_eq!(ranks.get("的"), Some(&1));
+        assert_eq!(ranks.get("我"), Some(&2));
+        assert_eq!(ranks.get("你"), Some(&3));
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::extraction::{contains_hanzi, word_to_hanzi, ExtractionResult};
+use crate::levels::WordLevel;
+use crate::segmentation::BookSegmentation;
+
+/// Amount of words per window of the moving-average type/token ratio
+pub const MATTR_WINDOW: usize = 500;
+
+/// Words ranked beyond this in the frequency list count as rare
+pub const TOP_N_WORDS: u64 = 5000;
+
+/// Lexical richness of a text, all shares are between 0 and 1
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct LexicalStats {
+    pub type_token_ratio: f64,
+    /// type/token ratio averaged over windows of MATTR_WINDOW words,
+    /// unlike the plain ratio it does not shrink with the length of the text
+    pub moving_average_ttr: f64,
+    /// share of unique words that occur only once
+    pub hapax_share: f64,
+    /// characters per word
+    pub mean_word_length: f64,
+    /// words per sentence, None for books segmented without sentences
+    pub mean_sentence_length: Option<f64>,
+    /// share of words outside the TOP_N_WORDS most frequent ones,
+    /// None if no frequency ranks are imported
+    pub outside_top_share: Option<f64>,
+}
+
+pub fn get_lexical_stats(
+    book: &BookSegmentation,
+    levels: &HashMap<String, WordLevel>,
+) -> LexicalStats {
+    let tokens: Vec<&str> = book
+        .chapter_cuts
+        .iter()
+        .flat_map(|chapter| &chapter.cut)
+        .map(|word| word.as_str())
+        .filter(|word| contains_hanzi(word))
+        .collect();
+    if tokens.is_empty() {
+        return LexicalStats::default();
+    }
+    let mut frequencies: HashMap<&str, u64> = HashMap::new();
+    for token in &tokens {
+        *frequencies.entry(token).or_insert(0) += 1;
+    }
+    let hapaxes = frequencies.values().filter(|f| **f == 1).count();
+    let chars: usize = tokens.iter().map(|token| word_to_hanzi(token).len()).sum();
+
+    let mut sentences = 0;
+    let mut sentence_words = 0;
+    for chapter in book
+        .chapter_cuts
+        .iter()
+        .filter(|chapter| !chapter.sentences.is_empty())
+    {
+        for sentence in chapter.sentence_cuts() {
+            let words = sentence.words.iter().filter(|w| contains_hanzi(w)).count();
+            if words > 0 {
+                sentences += 1;
+                sentence_words += words;
+            }
+        }
+    }
+
+    let has_ranks = levels.values().any(|level| level.rank.is_some());
+    let outside_top = tokens
+        .iter()
+        .filter(|token| {
+            levels
+                .get(**token)
+                .and_then(|level| level.rank)
+                .is_none_or(|rank| rank > TOP_N_WORDS)
+        })
+        .count();
+
+    LexicalStats {
+        type_token_ratio: frequencies.len() as f64 / tokens.len() as f64,
+        moving_average_ttr: moving_average_ttr(&tokens, MATTR_WINDOW),
+        hapax_share: hapaxes as f64 / frequencies.len() as f64,
+        mean_word_length: chars as f64 / tokens.len() as f64,
+        mean_sentence_length: (sentences > 0).then(|| sentence_words as f64 / sentences as f64),
+        outside_top_share: has_ranks.then(|| outside_top as f64 / tokens.len() as f64),
+    }
+}
+
+/// Lexical stats of an extracted book, using the levels its vocabulary is annotated with
+pub fn get_extraction_lexical_stats(extraction_res: &ExtractionResult) -> LexicalStats {
+    let levels: HashMap<String, WordLevel> = extraction_res
+        .vocabulary
+        .iter()
+        .map(|item| (item.word.clone(), item.level))
+        .collect();
+    get_lexical_stats(&extraction_res.segmented_book, &levels)
+}
+
+// texts shorter than the window get their plain type/token ratio
+fn moving_average_ttr(tokens: &[&str], window: usize) -> f64 {
+    if tokens.len() <= window {
+        let mut types: Vec<&&str> = tokens.iter().collect();
+        types.sort();
+        types.dedup();
+        return types.len() as f64 / tokens.len() as f64;
+    }
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for token in &tokens[..window] {
+        *counts.entry(token).or_insert(0) += 1;
+    }
+    let mut ratio_sum = counts.len() as f64 / window as f64;
+    for i in window..tokens.len() {
+        *counts.entry(tokens[i]).or_insert(0) += 1;
+        let leaving = tokens[i - window];
+        if let Some(count) = counts.get_mut(leaving) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(leaving);
+            }
+        }
+        ratio_sum += counts.len() as f64 / window as f64;
+    }
+    ratio_sum / (tokens.len() - window + 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::levels::WordLevel;
+    use crate::lexical::*;
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation, Sentence};
+
+    #[test]
+    fn lexical_stats_of_small_book() {
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: ["我", "喜欢", "猫", "。", "我", "爱", "猫", "。"]
+                    .iter()
+                    .map(|w| w.to_string())
+                    .collect(),
+                sentences: vec![
+                    Sentence {
+                        text: "我喜欢猫。".to_string(),
+                        cut_end: 4,
+                    },
+                    Sentence {
+                        text: "我爱猫。".to_string(),
+                        cut_end: 8,
+                    },
+                ],
+            }],
+        };
+        let levels = HashMap::from([
+            (
+                "我".to_string(),
+                WordLevel {
+                    level: None,
+                    rank: Some(3),
+                },
+            ),
+            (
+                "爱".to_string(),
+                WordLevel {
+                    level: None,
+                    rank: Some(9000),
+                },
+            ),
+        ]);
+        let stats = get_lexical_stats(&book, &levels);
+        assert!((stats.type_token_ratio - 4.0 / 6.0).abs() < 1e-9);
+        assert!((stats.hapax_share - 0.5).abs() < 1e-9);
+        assert!((stats.mean_word_length - 7.0 / 6.0).abs() < 1e-9);
+        assert_eq!(stats.mean_sentence_length, Some(3.0));
+        assert_eq!(stats.outside_top_share, Some(4.0 / 6.0));
+        assert!(get_lexical_stats(&book, &HashMap::new())
+            .outside_top_share
+            .is_none());
+    }
+
+    #[test]
+    fn moving_average_ttr_over_windows() {
+        let tokens = ["a", "b", "a", "b"];
+        // windows: [a b] [b a] [a b]
+        assert!((moving_average_ttr(&tokens, 2) - 1.0).abs() < 1e-9);
+        let tokens = ["a", "a", "a", "b"];
+        // windows: [a a a] [a a b]
+        assert!((moving_average_ttr(&tokens, 3) - 0.5).abs() < 1e-9);
+    }
+}
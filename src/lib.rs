@@ -1,11 +1,22 @@
 pub mod analysis;
 pub mod cli;
+pub mod components;
 pub mod config;
+pub mod corpus;
 pub mod db;
 pub mod ebook;
+pub mod examples;
 pub mod extraction;
 pub mod fan2jian;
+pub mod filter;
+pub mod grammar;
+pub mod history;
+pub mod import;
+pub mod levels;
+pub mod lexical;
+pub mod report;
 pub mod segmentation;
 pub mod tui;
+pub mod vocab_export;
 pub mod vocabulary;
 pub mod word_lists;
@@ -1,17 +1,23 @@
-use han_cihui::cli::{get_arg_matches, perform_add_external, perform_delete_external, show};
-use han_cihui::config::{get_data_dir, init_config};
+use han_cihui::cli::{
+    analyze_query_from_matches, get_arg_matches, perform_add_external, perform_analyze,
+    perform_delete_external, perform_export_history, perform_export_vocab, perform_ignore,
+    perform_import_levels, perform_import_ranks, perform_list_chars, perform_list_external,
+    perform_list_ignored, perform_list_levels, perform_mark_chars, perform_undo_external,
+    perform_unignore, perform_unmark_chars, perform_use_levels, show, AnalyzeInput,
+};
+use han_cihui::config::{get_config, get_data_dir, init_config};
+use han_cihui::db::db_migrate;
+use han_cihui::db::vocab::{ExternalSource, VocabStatus};
+use han_cihui::import::VocabFormat;
+use han_cihui::report::ReportFormat;
 use han_cihui::tui::state::TuiState;
 use han_cihui::tui::TuiApp;
+use han_cihui::vocab_export::VocabExportFormat;
 use rusqlite::Connection;
 use std::fs;
 
 use anyhow::Result;
 
-mod embedded {
-    use refinery::embed_migrations;
-    embed_migrations!("migrations_sql");
-}
-
 fn main() -> Result<()> {
     let data_dir = get_data_dir();
     if !data_dir.exists() {
@@ -25,21 +31,112 @@ fn main() -> Result<()> {
 
     let db_path = data_dir.join("data.db");
     let mut data_conn = Connection::open(db_path)?;
-    embedded::migrations::runner().run(&mut data_conn)?;
+    db_migrate(&mut data_conn)?;
 
     let matches = get_arg_matches();
     match matches.subcommand_name() {
         Some("add") => {
             let matches = matches.subcommand_matches("add").unwrap();
-            let filename = *matches.get_one("filename").unwrap();
-            perform_add_external(&data_conn, filename)
+            let filename: &String = matches.get_one("filename").unwrap();
+            let note = matches.get_one::<String>("note").map(|note| note.as_str());
+            let format: VocabFormat = *matches.get_one("format").unwrap();
+            let apply = matches.get_flag("apply");
+            perform_add_external(&mut data_conn, filename, format, note, apply)
         }
         Some("delete") => {
             let matches = matches.subcommand_matches("delete").unwrap();
-            let filename = *matches.get_one("filename").unwrap();
-            perform_delete_external(&data_conn, filename)
+            let filename: &String = matches.get_one("filename").unwrap();
+            perform_delete_external(&mut data_conn, filename)
         }
         Some("show") => show(&data_conn),
+        Some("ignore") => {
+            let matches = matches.subcommand_matches("ignore").unwrap();
+            let filename: &String = matches.get_one("filename").unwrap();
+            perform_ignore(&mut data_conn, filename)
+        }
+        Some("unignore") => {
+            let matches = matches.subcommand_matches("unignore").unwrap();
+            let filename: &String = matches.get_one("filename").unwrap();
+            perform_unignore(&mut data_conn, filename)
+        }
+        Some("list-ignored") => perform_list_ignored(&data_conn),
+        Some("mark-chars") => {
+            let matches = matches.subcommand_matches("mark-chars").unwrap();
+            let characters: &String = matches.get_one("characters").unwrap();
+            let known = !matches.get_flag("unknown");
+            perform_mark_chars(&mut data_conn, characters, known)
+        }
+        Some("unmark-chars") => {
+            let matches = matches.subcommand_matches("unmark-chars").unwrap();
+            let characters: &String = matches.get_one("characters").unwrap();
+            perform_unmark_chars(&mut data_conn, characters)
+        }
+        Some("list-chars") => perform_list_chars(&data_conn),
+        Some("list-external") => {
+            let matches = matches.subcommand_matches("list-external").unwrap();
+            perform_list_external(&data_conn, matches.get_one::<ExternalSource>("source"))
+        }
+        Some("export-history") => perform_export_history(&data_conn),
+        Some("export-vocab") => {
+            let matches = matches.subcommand_matches("export-vocab").unwrap();
+            let format: VocabExportFormat = *matches.get_one("format").unwrap();
+            let statuses = matches.get_one::<Vec<VocabStatus>>("status");
+            let include_chars = matches.get_flag("chars");
+            perform_export_vocab(
+                &data_conn,
+                format,
+                statuses.map(|statuses| statuses.as_slice()),
+                include_chars,
+            )
+        }
+        Some("undo-external") => {
+            let matches = matches.subcommand_matches("undo-external").unwrap();
+            let source: &ExternalSource = matches.get_one("source").unwrap();
+            perform_undo_external(&mut data_conn, source)
+        }
+        Some("import-levels") => {
+            let matches = matches.subcommand_matches("import-levels").unwrap();
+            let filename: &String = matches.get_one("filename").unwrap();
+            let source: &String = matches.get_one("source").unwrap();
+            let level: Option<u8> = matches.get_one("level").copied();
+            perform_import_levels(&mut data_conn, filename, source, level)
+        }
+        Some("list-levels") => perform_list_levels(&data_conn),
+        Some("use-levels") => {
+            let matches = matches.subcommand_matches("use-levels").unwrap();
+            let source: &String = matches.get_one("source").unwrap();
+            perform_use_levels(&mut data_conn, source)
+        }
+        Some("analyze") => {
+            let matches = matches.subcommand_matches("analyze").unwrap();
+            let input = match matches.get_one::<String>("path") {
+                Some(path) => AnalyzeInput::Path(path.clone()),
+                None => AnalyzeInput::Stored {
+                    title: matches.get_one::<String>("book").unwrap().clone(),
+                    author: matches.get_one::<String>("author").cloned(),
+                },
+            };
+            let query = analyze_query_from_matches(matches)?;
+            let print_words = matches.get_flag("words");
+            let format = matches.get_one::<ReportFormat>("format").copied();
+            let known_levels = matches
+                .get_one::<Vec<VocabStatus>>("known")
+                .cloned()
+                .unwrap_or(get_config().anki_levels.known);
+            perform_analyze(
+                &data_conn,
+                input,
+                &query,
+                &known_levels,
+                print_words,
+                format,
+            )
+        }
+        Some("import-ranks") => {
+            let matches = matches.subcommand_matches("import-ranks").unwrap();
+            let filename: &String = matches.get_one("filename").unwrap();
+            perform_import_ranks(&mut data_conn, filename)
+        }
         _ => TuiApp::new_stdout(TuiState::new(data_conn)?)?.run(),
     }
 }
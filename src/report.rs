@@ -0,0 +1,405 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::analysis::{
+    get_analysis_info, get_chapter_stats, get_filtered_extraction_items, AnalysisInfo,
+    AnalysisQuery,
+};
+use crate::extraction::{ExtractionItem, ExtractionResult};
+use crate::lexical::{get_extraction_lexical_stats, LexicalStats, TOP_N_WORDS};
+use crate::vocabulary::VocabularyContext;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 3] = [
+        ReportFormat::Json,
+        ReportFormat::Csv,
+        ReportFormat::Markdown,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Markdown => "md",
+        }
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            _ => Err(anyhow!(
+                "unknown report format {}, use json, csv or markdown",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct QueryReport {
+    pub query: String,
+    pub info: AnalysisInfo,
+}
+
+#[derive(Serialize)]
+pub struct ChapterReport {
+    pub title: String,
+    pub total_words: u64,
+    pub known_words: u64,
+    pub comprehension: f64,
+    pub new_unknown_words: u64,
+}
+
+#[derive(Serialize)]
+pub struct UnknownWordReport {
+    pub word: String,
+    pub frequency: u64,
+    pub chapters: usize,
+    pub level: Option<u8>,
+    pub rank: Option<u64>,
+}
+
+/// Analysis of a book for several queries, its chapters and unknown words
+#[derive(Serialize)]
+pub struct AnalysisReport {
+    pub title: String,
+    pub author: String,
+    pub queries: Vec<QueryReport>,
+    pub chapters: Vec<ChapterReport>,
+    pub lexical: LexicalStats,
+    /// unknown words of words_query, most frequent first
+    pub words_query: String,
+    pub unknown_words: Vec<UnknownWordReport>,
+}
+
+/// Queries every report includes, in addition to the one the unknown words are listed for
+pub fn default_report_queries() -> Vec<AnalysisQuery> {
+    [1, 2, 3, 5].into_iter().map(AnalysisQuery::new).collect()
+}
+
+// ignored words, if given, are left out of the unknown word counts and list
+pub fn build_report(
+    title: &str,
+    author: &str,
+    extraction_res: &ExtractionResult,
+    vocabulary: &VocabularyContext,
+    queries: &[AnalysisQuery],
+    words_query: &AnalysisQuery,
+) -> AnalysisReport {
+    let known_words = &vocabulary.known_words_and_chars;
+    let ignored_words = vocabulary.ignored_for_counts();
+    let char_overrides = &vocabulary.char_overrides;
+    let mut all_queries: Vec<&AnalysisQuery> = queries.iter().collect();
+    if !all_queries.contains(&words_query) {
+        all_queries.push(words_query);
+    }
+    let queries = all_queries
+        .into_iter()
+        .map(|query| QueryReport {
+            query: query.to_string(),
+            info: get_analysis_info(
+                extraction_res,
+                query,
+                known_words,
+                ignored_words,
+                char_overrides,
+            ),
+        })
+        .collect();
+    let chapters = get_chapter_stats(extraction_res, known_words)
+        .into_iter()
+        .map(|chapter| ChapterReport {
+            comprehension: chapter.comprehension(),
+            title: chapter.title,
+            total_words: chapter.total_words,
+            known_words: chapter.known_words,
+            new_unknown_words: chapter.new_unknown_words,
+        })
+        .collect();
+    let mut unknown: Vec<&ExtractionItem> =
+        get_filtered_extraction_items(extraction_res, words_query, known_words, char_overrides)
+            .into_iter()
+            .filter(|item| !known_words.contains(&item.word))
+            .filter(|item| ignored_words.is_none_or(|ignored| !ignored.contains(&item.word)))
+            .collect();
+    unknown.sort_by(|a, b| b.frequency.cmp(&a.frequency).then(a.word.cmp(&b.word)));
+    let unknown_words = unknown
+        .into_iter()
+        .map(|item| UnknownWordReport {
+            word: item.word.clone(),
+            frequency: item.frequency,
+            chapters: item.chapters_containing(),
+            level: item.level.level,
+            rank: item.level.rank,
+        })
+        .collect();
+    AnalysisReport {
+        title: title.to_string(),
+        author: author.to_string(),
+        queries,
+        chapters,
+        lexical: get_extraction_lexical_stats(extraction_res),
+        words_query: words_query.to_string(),
+        unknown_words,
+    }
+}
+
+pub fn render_report(report: &AnalysisReport, format: ReportFormat) -> Result<String> {
+    match format {
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        ReportFormat::Csv => Ok(render_csv(report)),
+        ReportFormat::Markdown => Ok(render_markdown(report)),
+    }
+}
+
+const INFO_COLUMNS: [&str; 10] = [
+    "total_words",
+    "total_chars",
+    "unique_words",
+    "unique_chars",
+    "unknown_total_words",
+    "unknown_total_chars",
+    "unknown_unique_words",
+    "unknown_unique_chars",
+    "guessable_total_words",
+    "guessable_unique_words",
+];
+
+fn info_values(info: &AnalysisInfo) -> [u64; 10] {
+    [
+        info.total_words,
+        info.total_chars,
+        info.unique_words,
+        info.unique_chars,
+        info.unknown_total_words,
+        info.unknown_total_chars,
+        info.unknown_unique_words,
+        info.unknown_unique_chars,
+        info.guessable_total_words,
+        info.guessable_unique_words,
+    ]
+}
+
+fn lexical_values(lexical: &LexicalStats) -> [(String, String); 6] {
+    [
+        (
+            "type_token_ratio".to_string(),
+            format!("{:.4}", lexical.type_token_ratio),
+        ),
+        (
+            "moving_average_ttr".to_string(),
+            format!("{:.4}", lexical.moving_average_ttr),
+        ),
+        (
+            "hapax_share".to_string(),
+            format!("{:.4}", lexical.hapax_share),
+        ),
+        (
+            "mean_word_length".to_string(),
+            format!("{:.4}", lexical.mean_word_length),
+        ),
+        (
+            "mean_sentence_length".to_string(),
+            optional(
+                lexical
+                    .mean_sentence_length
+                    .map(|len| format!("{:.4}", len)),
+            ),
+        ),
+        (
+            format!("outside_top_{}_share", TOP_N_WORDS),
+            optional(
+                lexical
+                    .outside_top_share
+                    .map(|share| format!("{:.4}", share)),
+            ),
+        ),
+    ]
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+// one uniform table of section,name,field,value rows, sections being
+// query, chapter, lexical (without name) and unknown_word
+fn render_csv(report: &AnalysisReport) -> String {
+    let mut csv = String::from("section,name,field,value\n");
+    let mut push_row = |section: &str, name: &str, field: &str, value: String| {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{}",
+            section,
+            csv_field(name),
+            field,
+            csv_field(&value)
+        );
+    };
+    for query in &report.queries {
+        for (field, value) in INFO_COLUMNS.iter().zip(info_values(&query.info)) {
+            push_row("query", &query.query, field, value.to_string());
+        }
+    }
+    for chapter in &report.chapters {
+        let fields = [
+            ("total_words", chapter.total_words.to_string()),
+            ("known_words", chapter.known_words.to_string()),
+            ("comprehension", format!("{:.4}", chapter.comprehension)),
+            ("new_unknown_words", chapter.new_unknown_words.to_string()),
+        ];
+        for (field, value) in fields {
+            push_row("chapter", &chapter.title, field, value);
+        }
+    }
+    for (metric, value) in lexical_values(&report.lexical) {
+        push_row("lexical", "", &metric, value);
+    }
+    for word in &report.unknown_words {
+        let fields = [
+            ("frequency", word.frequency.to_string()),
+            ("chapters", word.chapters.to_string()),
+            ("level", optional(word.level)),
+            ("rank", optional(word.rank)),
+        ];
+        for (field, value) in fields {
+            push_row("unknown_word", &word.word, field, value);
+        }
+    }
+    csv
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn render_markdown(report: &AnalysisReport) -> String {
+    let mut md = String::new();
+    if report.author.is_empty() {
+        let _ = writeln!(md, "# {}\n", report.title);
+    } else {
+        let _ = writeln!(md, "# {} ({})\n", report.title, report.author);
+    }
+    md.push_str("## Queries\n\n");
+    let _ = writeln!(md, "| query | {} |", INFO_COLUMNS.join(" | "));
+    let _ = writeln!(md, "|---{}|", "|---".repeat(INFO_COLUMNS.len()));
+    for query in &report.queries {
+        let values: Vec<String> = info_values(&query.info)
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        let _ = writeln!(md, "| {} | {} |", query.query, values.join(" | "));
+    }
+    md.push_str("\n## Chapters\n\n");
+    md.push_str("| # | chapter | words | comprehension | new unknown words |\n");
+    md.push_str("|---|---|---|---|---|\n");
+    for (i, chapter) in report.chapters.iter().enumerate() {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {:.3} | {} |",
+            i + 1,
+            chapter.title.replace('|', "\\|"),
+            chapter.total_words,
+            chapter.comprehension,
+            chapter.new_unknown_words
+        );
+    }
+    md.push_str("\n## Lexical richness\n\n| metric | value |\n|---|---|\n");
+    for (metric, value) in lexical_values(&report.lexical) {
+        let _ = writeln!(md, "| {} | {} |", metric, value);
+    }
+    let _ = writeln!(md, "\n## Unknown words ({})\n", report.words_query);
+    md.push_str("| word | frequency | chapters | level | rank |\n");
+    md.push_str("|---|---|---|---|---|\n");
+    for word in &report.unknown_words {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} |",
+            word.word,
+            word.frequency,
+            word.chapters,
+            optional(word.level),
+            optional(word.rank)
+        );
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::analysis::AnalysisQuery;
+    use crate::extraction::extract_vocab_from_segmented;
+    use crate::report::*;
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+    use crate::vocabulary::{CharOverrides, VocabularyContext};
+
+    #[test]
+    fn render_report_in_all_formats() {
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "第一章, 开始".to_string(),
+                cut: ["我", "猫", "猫", "狗"]
+                    .iter()
+                    .map(|w| w.to_string())
+                    .collect(),
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book);
+        let vocabulary = VocabularyContext {
+            known_words_and_chars: HashSet::from(["我".to_string()]),
+            char_overrides: CharOverrides::default(),
+            ignored_words: HashSet::new(),
+            exclude_ignored: false,
+        };
+        let report = build_report(
+            "书",
+            "",
+            &extraction_res,
+            &vocabulary,
+            &default_report_queries(),
+            &AnalysisQuery::new(2),
+        );
+        assert_eq!(report.queries.len(), 4);
+        let words: Vec<&str> = report
+            .unknown_words
+            .iter()
+            .map(|w| w.word.as_str())
+            .collect();
+        assert_eq!(words, vec!["猫"]);
+        let csv = render_report(&report, ReportFormat::Csv).unwrap();
+        assert!(csv.starts_with("section,name,field,value\n"));
+        assert!(csv.contains("chapter,\"第一章, 开始\",comprehension,0.2500\n"));
+        assert!(csv.contains("unknown_word,猫,frequency,2\n"));
+        assert!(csv.lines().all(|line| line.split(',').count() >= 4));
+        let md = render_report(&report, ReportFormat::Markdown).unwrap();
+        assert!(md.contains("| 猫 | 2 | 1 |  |  |"));
+        assert!(md.contains("| # | chapter | words | comprehension | new unknown words |"));
+        let json = render_report(&report, ReportFormat::Json).unwrap();
+        assert!(json.contains("\"unknown_total_words\": 3"));
+        assert!(json.contains("\"hapax_share\": 0.6666666666666666"));
+        assert!(csv.contains("lexical,,type_token_ratio,0.7500\n"));
+    }
+}
@@ -6,10 +6,49 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str;
 
+const SENTENCE_TERMINATORS: [char; 8] = ['。', '！', '？', '!', '?', '；', ';', '\n'];
+const CLOSING_QUOTES: [char; 6] = ['」', '』', '”', '’', '"', '）'];
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ChapterSegmentation {
     pub title: String,
     pub cut: Vec<String>,
+    /// Sentences of the chapter, empty for books that were segmented before sentences were kept
+    #[serde(default)]
+    pub sentences: Vec<Sentence>,
+}
+
+/// A sentence's original text and the (exclusive) end of its words within the chapter cut
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Sentence {
+    pub text: String,
+    pub cut_end: usize,
+}
+
+/// A sentence's text together with its segmented words
+pub struct SentenceCut<'a> {
+    pub text: &'a str,
+    pub words: &'a [String],
+}
+
+impl ChapterSegmentation {
+    /// Get the chapter's sentences with their words
+    ///
+    /// chapters without sentence information have none, their cut lacks the punctuation to split it
+    pub fn sentence_cuts(&self) -> Vec<SentenceCut<'_>> {
+        let mut start = 0;
+        self.sentences
+            .iter()
+            .map(|sentence| {
+                let words = &self.cut[start..sentence.cut_end];
+                start = sentence.cut_end;
+                SentenceCut {
+                    text: &sentence.text,
+                    words,
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -18,6 +57,17 @@ pub struct BookSegmentation {
     pub chapter_cuts: Vec<ChapterSegmentation>,
 }
 
+impl BookSegmentation {
+    /// Hash of the book's content (FNV-1a of its JSON), stable across runs
+    pub fn content_hash(&self) -> String {
+        let json = serde_json::to_string(self).expect("failed to serialize segmented book");
+        let hash = json.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        format!("{:016x}", hash)
+    }
+}
+
 pub fn segment_text(
     text: &str,
     jieba: &Jieba,
@@ -46,35 +96,87 @@ pub fn segment_text(
     segmented
 }
 
+/// Split text into sentences at sentence ending punctuation and line breaks
+///
+/// closing quotes directly following the punctuation are kept with the sentence
+pub fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !SENTENCE_TERMINATORS.contains(&c) {
+            continue;
+        }
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if SENTENCE_TERMINATORS.contains(&next) || CLOSING_QUOTES.contains(&next) {
+                end = j + next.len_utf8();
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let sentence = text[start..end].trim();
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+        start = end;
+    }
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest);
+    }
+    sentences
+}
+
+fn segment_chapter(
+    title: &str,
+    texts: &[&str],
+    segment: impl Fn(&str) -> Vec<String>,
+) -> ChapterSegmentation {
+    let mut cut = Vec::new();
+    let mut sentences = Vec::new();
+    for sentence in texts.iter().flat_map(|text| split_sentences(text)) {
+        let words = segment(sentence);
+        // sentences without any hanzi are irrelevant for analysis
+        if words.is_empty() {
+            continue;
+        }
+        cut.extend(words);
+        sentences.push(Sentence {
+            text: sentence.to_owned(),
+            cut_end: cut.len(),
+        });
+    }
+    ChapterSegmentation {
+        title: title.to_owned(),
+        cut,
+        sentences,
+    }
+}
+
 pub fn segment_book(book: &FlatBook) -> BookSegmentation {
     let jieba = Jieba::new();
     let fan2jian = get_mapping(true);
     let jian2fan = get_mapping(false);
 
-    let segment = |text: &String| segment_text(text, &jieba, &fan2jian, &jian2fan);
-
-    // preface cut include title and author
-    let mut preface_cut = segment(&book.preface_content);
-    preface_cut.extend(segment(&book.title));
-    preface_cut.extend(segment(&book.author));
+    let segment = |text: &str| segment_text(text, &jieba, &fan2jian, &jian2fan);
 
-    // preface is first chapter
+    // preface is first chapter, its cut includes title and author
     let mut chapter_segmentations: Vec<ChapterSegmentation> =
         Vec::with_capacity(book.chapters.len() + 1);
-    let preface_segmentation = ChapterSegmentation {
-        title: "Preface".to_owned(),
-        cut: preface_cut,
-    };
-    chapter_segmentations.push(preface_segmentation);
+    chapter_segmentations.push(segment_chapter(
+        "Preface",
+        &[&book.preface_content, &book.title, &book.author],
+        segment,
+    ));
 
     for chapter in &book.chapters {
-        let mut cut = segment(&chapter.content);
-        cut.extend(segment(&chapter.title));
-        let chapter_segmentation = ChapterSegmentation {
-            title: chapter.title.clone(),
-            cut,
-        };
-        chapter_segmentations.push(chapter_segmentation);
+        chapter_segmentations.push(segment_chapter(
+            &chapter.title,
+            &[&chapter.content, &chapter.title],
+            segment,
+        ));
     }
 
     BookSegmentation {
@@ -82,3 +184,23 @@ pub fn segment_book(book: &FlatBook) -> BookSegmentation {
         chapter_cuts: chapter_segmentations,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::segmentation::split_sentences;
+
+    #[test]
+    fn split_sentences_at_punctuation() {
+        let text = "他说：「你好！」我没回答。\n第二行……没有结尾";
+        assert_eq!(
+            split_sentences(text),
+            vec!["他说：「你好！」", "我没回答。", "第二行……没有结尾"]
+        );
+    }
+
+    #[test]
+    fn split_sentences_skips_empty() {
+        assert!(split_sentences("\n\n  ").is_empty());
+        assert_eq!(split_sentences("好。。\n"), vec!["好。。"]);
+    }
+}
@@ -4,9 +4,19 @@ use std::{cell::RefCell, rc::Rc};
 
 use han_cihui::config::tagging_socket_path;
 use han_cihui::word_lists::{Category, TaggedWord};
-use slint::quit_event_loop;
-
-slint::include_modules!();
+use slint::{quit_event_loop, ComponentHandle};
+
+// generated code of older slint versions trips newer lints
+#[allow(
+    dead_code,
+    unused_imports,
+    non_local_definitions,
+    mismatched_lifetime_syntaxes
+)]
+mod ui {
+    slint::include_modules!();
+}
+use ui::AppWindow;
 
 pub fn main() {
     let mut stream = UnixStream::connect(tagging_socket_path()).expect("could not open stream");
@@ -33,6 +43,8 @@ pub fn tag_words(words: &mut Vec<TaggedWord>) {
     let ui = AppWindow::new();
     let current_word = state.borrow().current_word().unwrap();
     ui.set_current_word(current_word.into());
+    ui.set_examples(state.borrow().current_examples().into());
+    ui.set_level(state.borrow().current_level().into());
     ui.set_footer(get_footer_tagging().into());
 
     // closure for updating state & ui
@@ -48,6 +60,8 @@ pub fn tag_words(words: &mut Vec<TaggedWord>) {
                 .unwrap_or_else(|| "END".to_string())
                 .into(),
         );
+        ui.set_examples(state_clone.borrow().current_examples().into());
+        ui.set_level(state_clone.borrow().current_level().into());
         if state_clone.borrow().current_word().is_none() {
             ui.set_footer(get_footer_finished().into());
         } else {
@@ -118,6 +132,20 @@ impl State {
         self.words.get(self.index).map(|w| w.word.clone())
     }
 
+    fn current_examples(&self) -> String {
+        self.words
+            .get(self.index)
+            .map(|w| w.examples.join("\n"))
+            .unwrap_or_default()
+    }
+
+    fn current_level(&self) -> String {
+        self.words
+            .get(self.index)
+            .map(|w| w.level.description())
+            .unwrap_or_default()
+    }
+
     fn get_words(&self) -> Vec<TaggedWord> {
         self.words.clone()
     }
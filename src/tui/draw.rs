@@ -1,7 +1,9 @@
 mod analysis;
 mod books;
 mod info;
+mod library;
 mod util;
+mod vocabulary;
 mod word_list;
 
 use anyhow::{Context, Result};
@@ -15,10 +17,14 @@ use tui::{
 };
 use tui::{
     text::Span,
-    widgets::{Block, BorderType, Borders, Paragraph, Tabs},
+    widgets::{Block, BorderType, Borders, Paragraph, Tabs, Wrap},
 };
 
-use self::books::{draw_books_display, draw_books_importing, draw_books_loading};
+use self::books::{
+    draw_books_comparing, draw_books_display, draw_books_importing, draw_books_loading,
+};
+use self::library::draw_library_display;
+use self::vocabulary::draw_vocabulary_display;
 use self::word_list::{draw_opened_word_list, draw_word_lists};
 use self::{
     analysis::{draw_analysis_blank, draw_analysis_extracted},
@@ -26,9 +32,11 @@ use self::{
     util::get_wrapping_spans,
 };
 
-use super::state::analysis::AnalysisState;
+use super::state::analysis::{AnalysisSection, AnalysisState};
 use super::state::books::BooksState;
 use super::state::info::InfoState;
+use super::state::library::{LibrarySection, LibraryState};
+use super::state::vocabulary::{VocabularyInput, VocabularyState};
 use super::state::word_list::WordListState;
 use super::state::{TuiState, View};
 
@@ -56,7 +64,8 @@ pub(super) fn draw_window(
                     [
                         Constraint::Length(3),
                         Constraint::Min(2),
-                        Constraint::Length(3),
+                        // footer fits two lines of key bindings
+                        Constraint::Length(4),
                     ]
                     .as_ref(),
                 )
@@ -81,12 +90,20 @@ fn draw_inner(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState,
             }
         },
         View::Info => match &state.info_state {
-            InfoState::Display(display_state) => draw_info(frame, &display_state.vocab_info, area),
+            InfoState::Display(display_state) => draw_info(
+                frame,
+                &display_state.vocab_info,
+                &display_state.history,
+                area,
+            ),
             InfoState::Syncing(syncing_state) => draw_info_syncing(frame, syncing_state, area),
             // TODO: add visual indicator that sync error occurred
-            InfoState::SyncError(sync_error_state) => {
-                draw_info(frame, &sync_error_state.previous_vocab_info, area)
-            }
+            InfoState::SyncError(sync_error_state) => draw_info(
+                frame,
+                &sync_error_state.previous_vocab_info,
+                &sync_error_state.previous_history,
+                area,
+            ),
         },
         View::WordLists => match &state.word_list_state {
             WordListState::List(lists_state) => draw_word_lists(frame, lists_state, area),
@@ -101,6 +118,9 @@ fn draw_inner(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState,
                 area,
             ),
             BooksState::Display(display_state) => draw_books_display(frame, display_state, area),
+            BooksState::Comparing(comparing_state) => {
+                draw_books_comparing(frame, comparing_state, area)
+            }
             BooksState::EnterToImport(partial_path) => {
                 draw_books_importing(frame, partial_path, area)
             }
@@ -111,6 +131,24 @@ fn draw_inner(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState,
                 area,
             ),
         },
+        View::Library => match &state.library_state {
+            LibraryState::Uninitialized => draw_books_loading(frame, "loading", 0, area),
+            LibraryState::Calculating(calculating_state) => draw_books_loading(
+                frame,
+                "indexing library",
+                calculating_state.elapsed().as_secs(),
+                area,
+            ),
+            LibraryState::Display(display_state) => {
+                draw_library_display(frame, display_state, area)
+            }
+        },
+        View::Vocabulary => match &state.vocabulary_state {
+            VocabularyState::Uninitialized => draw_books_loading(frame, "loading", 0, area),
+            VocabularyState::Display(display_state) => {
+                draw_vocabulary_display(frame, display_state, area)
+            }
+        },
         View::Exit => {}
     }
 }
@@ -121,6 +159,8 @@ fn draw_header(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState
         "Books [1]".to_string(),
         "Analysis [2]".to_string(),
         "Word Lists [3]".to_string(),
+        "Library [4]".to_string(),
+        "Words [5]".to_string(),
     ]
     .into_iter()
     .map(|s| Spans::from(Span::styled(s, Style::default().fg(Color::Yellow))))
@@ -130,6 +170,8 @@ fn draw_header(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState
         View::Books => 1,
         View::Analysis => 2,
         View::WordLists => 3,
+        View::Library => 4,
+        View::Vocabulary => 5,
         View::Exit => 0,
     };
     let tabs = Tabs::new(tab_titles)
@@ -146,21 +188,63 @@ fn draw_header(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState
 fn draw_footer(frame: &mut Frame<CrosstermBackend<impl Write>>, state: &TuiState, area: Rect) {
     let text = match state.current_view {
         View::Info => "[S]: sync Anki | [Q]: exit",
-        View::Books => "[I]: import new book | [Enter]: analyze",
-        View::Analysis => {
-            "[J]: - #word | [K]: + #word | [H]: - #char | [L]: + #char | [S]: save | [R]: reset"
-        }
+        View::Books => match &state.books_state {
+            BooksState::Comparing(_) => "[ESC]: back to books | [J]: down | [K]: up",
+            _ => "[I]: import new book | [Enter]: analyze | [M]: mark | [C]: compare with marked | [D]: delete",
+        },
+        View::Analysis => match &state.analysis_state {
+            AnalysisState::Extracted(extracted_state) => match extracted_state.section {
+                AnalysisSection::Grammar => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [[/]]: pattern | [G]: export grammar | [Tab]: words | [R]: reset"
+                }
+                AnalysisSection::Words => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [[/]]: reading position | [Tab]: characters | [R]: reset"
+                }
+                AnalysisSection::Characters => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [C]: save character list | [Tab]: components | [R]: reset"
+                }
+                AnalysisSection::Components => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [Tab]: coverage | [R]: reset"
+                }
+                AnalysisSection::Coverage => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [-/+]: target | [P]: save plan | [Tab]: chapters | [R]: reset"
+                }
+                AnalysisSection::Chapters => {
+                    "[J/K]: -/+ #word | [H/L]: -/+ #char | [</>]: -/+ level | [X]: guessable | [V]: known levels | [F]: filter | [S]: save | [E]: report | [Tab]: grammar | [R]: reset"
+                }
+            },
+            AnalysisState::Blank => "[1]: go to books",
+        },
         View::WordLists => match &state.word_list_state {
             WordListState::List(_) => "[Enter]: select | [J]: down | [K]: up | [D]: delete",
             WordListState::Opened(_) => {
-                "[ESC]: overview | [Enter]: filter | [J]: down | [K]: up, | [E]: export"
+                "[ESC]: overview | [Enter]: filter | [J]: down | [K]: up, | [E]: export | [I]: ignore tagged | [U]: unignore tagged"
             }
         },
+        View::Library => match &state.library_state {
+            LibraryState::Display(display_state)
+                if display_state.section == LibrarySection::ReadingOrder =>
+            {
+                "[J]: down | [K]: up | [X]: exclude/include book | [-/+]: target | [Tab]: words | [R]: recompute"
+            }
+            _ => "[J]: down | [K]: up | [S]: save as word list | [E]: export | [Tab]: reading order | [R]: recompute",
+        },
+        View::Vocabulary => match &state.vocabulary_state {
+            VocabularyState::Display(display_state) => match display_state.input {
+                Some(VocabularyInput::Search) => "[Enter]: keep search | [ESC]: clear search",
+                Some(VocabularyInput::Add(_)) => "[Enter]: add word | [ESC]: cancel",
+                Some(VocabularyInput::MarkChars(..)) => "[Enter]: mark characters | [ESC]: cancel",
+                Some(VocabularyInput::UnmarkChars(_)) => "[Enter]: unmark characters | [ESC]: cancel",
+                None => "[J]: down | [K]: up | [/]: search | [ESC]: clear search | [A]: add word | [D]: delete added word | [C]/[U]: mark chars known/unknown | [R]: unmark chars",
+            },
+            VocabularyState::Uninitialized => "",
+        },
         View::Exit => "EXITING",
     };
     let paragraph = Paragraph::new(text)
         .style(Style::default().fg(Color::LightGreen))
         .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .borders(Borders::ALL)
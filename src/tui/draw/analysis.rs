@@ -1,18 +1,22 @@
+use crate::config::get_config;
+use crate::db::vocab::describe_known_levels;
+use crate::lexical::TOP_N_WORDS;
 use crate::tui::draw::util::{
-    get_analysis_info_percentage_table, get_analysis_info_table, get_centered_rect, split_to_lines,
+    draw_centered_input, get_analysis_info_percentage_table, get_analysis_info_table,
+    get_centered_rect, get_sparkline_string, split_to_lines,
 };
-use crate::tui::state::analysis::ExtractedState;
+use crate::tui::state::analysis::{AnalysisSection, ExtractedState};
 use std::io::Write;
 use tui::layout::{Alignment, Constraint, Direction, Layout};
 use tui::text::Spans;
 use tui::{backend::CrosstermBackend, layout::Rect};
 use tui::{
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     Frame,
 };
 use tui::{
     text::Span,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
 };
 
 pub fn draw_analysis_extracted(
@@ -22,9 +26,20 @@ pub fn draw_analysis_extracted(
 ) {
     let info_all = state.query_all();
     let info_min_occ = state.query_current();
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            [
+                Constraint::Length(8),
+                Constraint::Length(2),
+                Constraint::Min(4),
+            ]
+            .as_ref(),
+        )
+        .split(area);
     let layout = Layout::default()
         .direction(Direction::Horizontal)
-        .margin(2)
         .constraints(
             [
                 Constraint::Percentage(33),
@@ -33,11 +48,14 @@ pub fn draw_analysis_extracted(
             ]
             .as_ref(),
         );
-    let chunks = layout.split(area);
+    let chunks = layout.split(vertical_chunks[0]);
     let block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::White))
-        .title("词/字 occurrence info");
+        .title(format!(
+            "词/字 occurrence info (known: {})",
+            describe_known_levels(&state.known_levels)
+        ));
     frame.render_widget(block, area);
     let all_chunk = chunks[0];
     let min_occ_chunk = chunks[1];
@@ -46,13 +64,22 @@ pub fn draw_analysis_extracted(
         get_analysis_info_table(&info_all, "all words".to_string()),
         all_chunk,
     );
-    let min_occ_title = match state.analysis_query.min_occurrence_unknown_chars {
+    let mut min_occ_title = match state.analysis_query.min_occurrence_unknown_chars {
         Some(amount) => format!(
             "#word >= {} OR contains unknown #char >= {}",
             state.analysis_query.min_occurrence_words, amount
         ),
         None => format!("#word >= {}", state.analysis_query.min_occurrence_words),
     };
+    if let Some(level) = state.analysis_query.max_level {
+        min_occ_title = format!("({}) AND level <= {}", min_occ_title, level);
+    }
+    if state.analysis_query.exclude_guessable {
+        min_occ_title = format!("({}) AND not guessable", min_occ_title);
+    }
+    if let Some(filter) = &state.analysis_query.filter {
+        min_occ_title = format!("({}) AND ({})", min_occ_title, filter.source());
+    }
     frame.render_widget(
         get_analysis_info_table(&info_min_occ, min_occ_title),
         min_occ_chunk,
@@ -61,6 +88,414 @@ pub fn draw_analysis_extracted(
         get_analysis_info_percentage_table(&info_all, &info_min_occ),
         perc_chunk,
     );
+    let guessable_line = format!(
+        "Unknown words: {} ({} occurrences) with only known characters, {} ({} occurrences) with unknown characters",
+        info_min_occ.guessable_unique_words,
+        info_min_occ.guessable_total_words,
+        info_min_occ.new_char_unique_words(),
+        info_min_occ.new_char_total_words()
+    );
+    let lexical = &state.lexical_stats;
+    let lexical_line = format!(
+        "TTR {:.3} | MATTR {:.3} | hapax {:.3} | {:.2} chars/word | {} words/sentence | outside top {}: {}",
+        lexical.type_token_ratio,
+        lexical.moving_average_ttr,
+        lexical.hapax_share,
+        lexical.mean_word_length,
+        lexical
+            .mean_sentence_length
+            .map_or("-".to_string(), |len| format!("{:.1}", len)),
+        TOP_N_WORDS,
+        lexical
+            .outside_top_share
+            .map_or("-".to_string(), |share| format!("{:.3}", share)),
+    );
+    frame.render_widget(
+        Paragraph::new(vec![Spans::from(guessable_line), Spans::from(lexical_line)])
+            .alignment(Alignment::Center),
+        vertical_chunks[1],
+    );
+    let section_chunk = vertical_chunks[2];
+    match state.section {
+        AnalysisSection::Grammar => draw_grammar(frame, state, section_chunk),
+        AnalysisSection::Words => {
+            frame.render_widget(get_unknown_words_table(state, section_chunk), section_chunk)
+        }
+        AnalysisSection::Characters => {
+            frame.render_widget(get_unknown_chars_table(state), section_chunk)
+        }
+        AnalysisSection::Components => draw_components(frame, state, section_chunk),
+        AnalysisSection::Coverage => {
+            frame.render_widget(get_coverage_plan_table(state), section_chunk)
+        }
+        AnalysisSection::Chapters => draw_chapter_stats(frame, state, section_chunk),
+    }
+    if let Some(filter_input) = &state.filter_input {
+        frame.render_widget(Clear, get_centered_rect(area));
+        draw_centered_input(
+            frame,
+            area,
+            filter_input,
+            "Filter (e.g freq <= 10 and len >= 2 and not proper), @name for preset",
+        );
+    }
+}
+
+fn draw_chapter_stats(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &ExtractedState,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(4)].as_ref())
+        .split(area);
+    let threshold = get_config().comprehension_threshold as f64 / 100.0;
+    // comprehension in per mille on a fixed 0-1000 scale, so that chapters of different books compare
+    let comprehension: Vec<u64> = state
+        .chapter_stats
+        .iter()
+        .map(|chapter| (chapter.comprehension() * 1000.0).round() as u64)
+        .collect();
+    let sparkline = get_sparkline_string(
+        &comprehension,
+        chunks[0].width.saturating_sub(2) as usize,
+        Some(1000),
+    );
+    frame.render_widget(
+        Paragraph::new(sparkline).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Known words per chapter"),
+        ),
+        chunks[0],
+    );
+
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = ["#", "Chapter", "Known", "New words", ""]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    // bar column takes up 40% of the table
+    let bar_width = (chunks[1].width as usize * 2 / 5).saturating_sub(2);
+    let rows = state.chapter_stats.iter().enumerate().map(|(i, chapter)| {
+        let comprehension = chapter.comprehension();
+        let style = if comprehension < threshold {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default()
+        };
+        let bar = "█".repeat((comprehension * bar_width as f64).round() as usize);
+        Row::new(vec![
+            Cell::from((i + 1).to_string()),
+            Cell::from(chapter.title.clone()),
+            Cell::from(format!("{:.3}", comprehension)),
+            Cell::from(chapter.new_unknown_words.to_string()),
+            Cell::from(bar),
+        ])
+        .style(style)
+    });
+    let below_threshold = state
+        .chapter_stats
+        .iter()
+        .filter(|chapter| chapter.comprehension() < threshold)
+        .count();
+    let title = format!(
+        "Chapters: {} of {} below {:.0}% known words",
+        below_threshold,
+        state.chapter_stats.len(),
+        threshold * 100.0
+    );
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(5),
+            Constraint::Percentage(30),
+            Constraint::Percentage(10),
+            Constraint::Percentage(15),
+            Constraint::Percentage(40),
+        ]);
+    frame.render_widget(table, chunks[1]);
+}
+
+fn get_unknown_chars_table(state: &ExtractedState) -> Table<'_> {
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = ["字", "Frequency", "#Words", "Top words"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = state.unknown_chars.iter().map(|info| {
+        let top_words = info
+            .words
+            .iter()
+            .take(5)
+            .map(|(word, freq)| format!("{}({})", word, freq))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let cells = vec![
+            Cell::from(info.hanzi.clone()),
+            Cell::from(info.frequency.to_string()),
+            Cell::from(info.words.len().to_string()),
+            Cell::from(top_words),
+        ];
+        Row::new(cells)
+    });
+    let title = format!("Unknown characters: {}", state.unknown_chars.len());
+    Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(10),
+            Constraint::Percentage(15),
+            Constraint::Percentage(10),
+            Constraint::Percentage(65),
+        ])
+}
+
+// unknown characters ranked by known components, next to their phonetic series
+fn draw_components(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &ExtractedState,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+        .split(area);
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let analysis = &state.component_analysis;
+
+    let header_cells = ["字", "IDS", "#Known", "Known components", "Phonetic"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = analysis.chars.iter().map(|info| {
+        let known_components = info
+            .known_components
+            .iter()
+            .map(|(component, examples)| {
+                if examples.is_empty() {
+                    component.clone()
+                } else {
+                    format!("{}({})", component, examples.join(""))
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+        let cells = vec![
+            Cell::from(info.hanzi.clone()),
+            Cell::from(info.ids.clone().unwrap_or_default()),
+            Cell::from(info.known_components.len().to_string()),
+            Cell::from(known_components),
+            Cell::from(info.phonetic.clone().unwrap_or_default()),
+        ];
+        Row::new(cells)
+    });
+    let decomposed = analysis.chars.iter().filter(|c| c.ids.is_some()).count();
+    let mut title = format!(
+        "Unknown characters by known components ({} of {} decomposed)",
+        decomposed,
+        analysis.chars.len()
+    );
+    if let Some(e) = &state.ids_error {
+        title.push_str(&format!(" - using bundled IDS data, {}", e));
+    }
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(8),
+            Constraint::Percentage(17),
+            Constraint::Percentage(10),
+            Constraint::Percentage(50),
+            Constraint::Percentage(15),
+        ]);
+    frame.render_widget(table, chunks[0]);
+
+    let header_cells = ["Phonetic", "Unknown", "Known"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = analysis.phonetic_groups.iter().map(|group| {
+        Row::new(vec![
+            Cell::from(group.phonetic.clone()),
+            Cell::from(group.unknown.join("")),
+            Cell::from(group.known.join("")),
+        ])
+    });
+    let title = format!("Phonetic series: {}", analysis.phonetic_groups.len());
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(20),
+            Constraint::Percentage(40),
+            Constraint::Percentage(40),
+        ]);
+    frame.render_widget(table, chunks[1]);
+}
+
+fn get_coverage_plan_table(state: &ExtractedState) -> Table<'_> {
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = ["#", "Word", "Frequency", "Coverage"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = state.coverage_plan.iter().enumerate().map(|(i, step)| {
+        let cells = vec![
+            Cell::from((i + 1).to_string()),
+            Cell::from(step.word.clone()),
+            Cell::from(step.frequency.to_string()),
+            Cell::from(format!("{:.3}", step.cumulative_coverage)),
+        ];
+        Row::new(cells)
+    });
+    let title = format!(
+        "Coverage plan: learn {} words to go from {:.3} to {}% known words",
+        state.coverage_plan.len(),
+        state.word_coverage,
+        state.coverage_target
+    );
+    Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(10),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ])
+}
+
+fn get_unknown_words_table(state: &ExtractedState, area: Rect) -> Table<'_> {
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = [
+        "Word",
+        "Level",
+        "Total",
+        "Upcoming",
+        "Chapters",
+        "Dispersion",
+    ]
+    .iter()
+    .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    // dispersion column takes up 40% of the table
+    let sparkline_width = (area.width as usize * 2 / 5).saturating_sub(2);
+    let upcoming = state.upcoming_chapters();
+    let rows = state.unknown_words.iter().map(|item| {
+        let cells = vec![
+            Cell::from(item.word.clone()),
+            Cell::from(item.level.description()),
+            Cell::from(item.frequency.to_string()),
+            Cell::from(item.frequency_in_chapters(upcoming.clone()).to_string()),
+            Cell::from(item.chapters_containing().to_string()),
+            Cell::from(get_sparkline_string(
+                &item.chapter_frequencies,
+                sparkline_width,
+                None,
+            )),
+        ];
+        Row::new(cells)
+    });
+    let title = format!(
+        "Unknown words, upcoming chapters {}-{} of {}",
+        upcoming.start + 1,
+        upcoming.end,
+        state.chapter_amount()
+    );
+    Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(40),
+        ])
+}
+
+// grammar patterns with the chapters the selected one occurs in
+fn draw_grammar(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &ExtractedState,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(area);
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let header_cells = ["Pattern", "Total", "Chapters", "Example"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = state
+        .grammar_occurrences
+        .iter()
+        .enumerate()
+        .map(|(i, occ)| {
+            let cells = vec![
+                Cell::from(occ.pattern_name.clone()),
+                Cell::from(occ.total().to_string()),
+                Cell::from(occ.chapters_containing().to_string()),
+                Cell::from(occ.examples.first().cloned().unwrap_or_default()),
+            ];
+            let row = Row::new(cells);
+            if i == state.selected_grammar {
+                row.style(selected_style)
+            } else {
+                row
+            }
+        });
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Grammar"))
+        .widths(&[
+            Constraint::Percentage(20),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(60),
+        ]);
+    frame.render_widget(table, chunks[0]);
+
+    let selected = state.grammar_occurrences.get(state.selected_grammar);
+    let chapter_titles = state.chapter_titles();
+    let breakdown = selected.map_or(vec![], |occ| occ.chapter_breakdown(&chapter_titles));
+    let header_cells = ["Chapter", "#"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let rows = breakdown.into_iter().map(|(title, count)| {
+        Row::new(vec![
+            Cell::from(title.to_string()),
+            Cell::from(count.to_string()),
+        ])
+    });
+    let title = selected.map_or("Chapters".to_string(), |occ| {
+        format!("Chapters with {}", occ.pattern_name)
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .widths(&[Constraint::Percentage(80), Constraint::Percentage(20)]);
+    frame.render_widget(table, chunks[1]);
 }
 
 pub fn draw_analysis_blank(frame: &mut Frame<CrosstermBackend<impl Write>>, area: Rect) {
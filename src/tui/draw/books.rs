@@ -3,8 +3,9 @@ use std::io::Write;
 
 use tui::{
     backend::CrosstermBackend,
-    layout::{Alignment, Constraint, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    text::Spans,
     widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
@@ -42,18 +43,37 @@ pub fn draw_books_display(
     let header_style = Style::default()
         .fg(Color::Yellow)
         .add_modifier(Modifier::BOLD);
-    let header_cells = ["Book", "Author", "Comprehension", "Length"]
-        .iter()
-        .map(|h| Cell::from(*h).style(header_style));
+    let header_cells = [
+        "Book",
+        "Author",
+        "Comprehension",
+        "Length",
+        "MATTR",
+        "Hapax",
+        "Rare words",
+    ]
+    .iter()
+    .map(|h| Cell::from(*h).style(header_style));
     let header = Row::new(header_cells).height(1).bottom_margin(1);
 
     let selected_style = Style::default().add_modifier(Modifier::REVERSED);
-    let rows = state.books_with_stats.iter().map(|b| {
+    let rows = state.books_with_stats.iter().enumerate().map(|(i, b)| {
+        let title = if state.marked == Some(i) {
+            format!("[A] {}", b.title)
+        } else {
+            b.title.clone()
+        };
         let cells = vec![
-            Cell::from(b.title.clone()),
+            Cell::from(title),
             Cell::from(b.author.clone()),
-            Cell::from(format!("{}", b.word_comprehension)),
-            Cell::from(format!("{} chars", b.total_chars)),
+            Cell::from(format!("{}", b.stats.word_comprehension)),
+            Cell::from(format!("{} chars", b.stats.total_chars)),
+            Cell::from(format!("{:.3}", b.stats.lexical.moving_average_ttr)),
+            Cell::from(format!("{:.3}", b.stats.lexical.hapax_share)),
+            Cell::from(match b.stats.lexical.outside_top_share {
+                Some(share) => format!("{:.3}", share),
+                None => "-".to_string(),
+            }),
         ];
         Row::new(cells)
     });
@@ -64,9 +84,12 @@ pub fn draw_books_display(
         .highlight_symbol(">> ")
         .widths(&[
             Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+            Constraint::Percentage(20),
+            Constraint::Percentage(15),
+            Constraint::Percentage(13),
+            Constraint::Percentage(9),
+            Constraint::Percentage(9),
+            Constraint::Percentage(9),
         ]);
     frame.render_stateful_widget(table, area, &mut state.table_state.borrow_mut());
 }
@@ -78,3 +101,64 @@ pub fn draw_books_importing(
 ) {
     draw_centered_input(frame, area, partial_path, "Path to epub file to import")
 }
+
+pub fn draw_books_comparing(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &books::ComparingState,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(4)].as_ref())
+        .split(area);
+    let comparison = &state.comparison;
+    let summary = vec![
+        Spans::from(format!(
+            "A: {} | known {:.3} | {} unique unknown words",
+            state.title_a, comparison.coverage_a, comparison.unique_unknown_a
+        )),
+        Spans::from(format!(
+            "B: {} | known {:.3} | {} unique unknown words",
+            state.title_b, comparison.coverage_b, comparison.unique_unknown_b
+        )),
+        Spans::from(format!(
+            "learning A's unknown words: B {:.3} -> {:.3} | learning B's unknown words: A {:.3} -> {:.3}",
+            comparison.coverage_b,
+            comparison.coverage_b_after_a,
+            comparison.coverage_a,
+            comparison.coverage_a_after_b
+        )),
+    ];
+    let paragraph = Paragraph::new(summary)
+        .block(Block::default().borders(Borders::ALL).title("Comparison"))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, chunks[0]);
+
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = ["Word", "Frequency A", "Frequency B"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let rows = comparison.shared_unknown.iter().map(|w| {
+        Row::new(vec![
+            Cell::from(w.word.clone()),
+            Cell::from(w.frequency_a.to_string()),
+            Cell::from(w.frequency_b.to_string()),
+        ])
+    });
+    let title = format!("Shared unknown words: {}", comparison.shared_unknown.len());
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(selected_style)
+        .highlight_symbol(">> ")
+        .widths(&[
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ]);
+    frame.render_stateful_widget(table, chunks[1], &mut state.table_state.borrow_mut());
+}
@@ -1,5 +1,10 @@
+use std::time::SystemTime;
+
+use crate::history::{format_date, get_daily_known_words, get_growth, Growth, MONTH, WEEK};
 use crate::tui::state::info::SyncingState;
-use crate::{tui::draw::util::get_centered_rect, vocabulary::VocabularyInfo};
+use crate::{
+    history::VocabSnapshot, tui::draw::util::get_centered_rect, vocabulary::VocabularyInfo,
+};
 
 use std::io::Write;
 use tui::layout::{Alignment, Constraint, Direction, Layout};
@@ -10,7 +15,7 @@ use tui::{
     widgets::{Clear, Wrap},
 };
 
-use tui::widgets::{Block, Borders, Paragraph};
+use tui::widgets::{BarChart, Block, Borders, Paragraph, Sparkline};
 use tui::{
     style::{Color, Style},
     Frame,
@@ -19,13 +24,26 @@ use tui::{
 pub fn draw_info(
     frame: &mut Frame<CrosstermBackend<impl Write>>,
     vocab_info: &VocabularyInfo,
+    history: &[VocabSnapshot],
     area: Rect,
 ) {
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(2)
+        .constraints(
+            [
+                Constraint::Length(5),
+                Constraint::Length(6),
+                Constraint::Min(6),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
+        .split(area);
     let layout = Layout::default()
         .direction(Direction::Horizontal)
-        .margin(2)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref());
-    let chunks = layout.split(area);
+    let chunks = layout.split(vertical_chunks[0]);
     let block = Block::default()
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::White))
@@ -51,6 +69,78 @@ pub fn draw_info(
     frame.render_widget(block, area);
     frame.render_widget(words_paragraph, chunks[0]);
     frame.render_widget(chars_paragraph, chunks[1]);
+    draw_growth(frame, history, &vertical_chunks[1..]);
+}
+
+// amount of weeks and months shown as bars
+const GROWTH_PERIODS: usize = 8;
+
+// sparkline of known words, bars of weekly and monthly growth, summary of the latest growth
+fn draw_growth(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    history: &[VocabSnapshot],
+    areas: &[Rect],
+) {
+    let now = SystemTime::now();
+    let days = areas[0].width.saturating_sub(2) as usize;
+    let daily = get_daily_known_words(history, days, now);
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(format!("Known words, last {} days", days))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::Green))
+        .data(&daily);
+    frame.render_widget(sparkline, areas[0]);
+
+    let bar_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(areas[1]);
+    let weekly = get_growth(history, WEEK, GROWTH_PERIODS, now);
+    let monthly = get_growth(history, MONTH, GROWTH_PERIODS, now);
+    for (growth, title, area) in [
+        (&weekly, "New words per week", bar_chunks[0]),
+        (&monthly, "New words per 30 days", bar_chunks[1]),
+    ] {
+        // label bars by the period's end date without year
+        let labels: Vec<String> = growth
+            .iter()
+            .map(|g| format_date(g.end)[5..].to_string())
+            .collect();
+        let data: Vec<(&str, u64)> = labels
+            .iter()
+            .zip(growth)
+            .map(|(label, g)| (label.as_str(), g.words.max(0) as u64))
+            .collect();
+        let bar_width = (area.width.saturating_sub(2) / GROWTH_PERIODS as u16)
+            .saturating_sub(1)
+            .max(5);
+        let chart = BarChart::default()
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .data(&data)
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Cyan))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+        frame.render_widget(chart, area);
+    }
+
+    let describe = |growth: Option<&Growth>| {
+        growth.map_or("-".to_string(), |g| {
+            format!("{:+} words, {:+} chars", g.words, g.chars)
+        })
+    };
+    let summary = format!(
+        "last 7 days: {} | last 30 days: {}",
+        describe(weekly.last()),
+        describe(monthly.last())
+    );
+    frame.render_widget(
+        Paragraph::new(summary).alignment(Alignment::Center),
+        areas[2],
+    );
 }
 
 pub fn draw_info_syncing(
@@ -0,0 +1,177 @@
+use std::io::Write;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+use crate::tui::state::library::{DisplayState, LibrarySection, PLANNER_MIN_FREQUENCY};
+
+pub fn draw_library_display(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &DisplayState,
+    area: Rect,
+) {
+    match state.section {
+        LibrarySection::Words => draw_library_words(frame, state, area),
+        LibrarySection::ReadingOrder => draw_reading_order(frame, state, area),
+    }
+}
+
+fn draw_reading_order(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &DisplayState,
+    area: Rect,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(6), Constraint::Length(6)].as_ref())
+        .split(area);
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = [
+        "#",
+        "Book",
+        "Author",
+        "Projected known",
+        "Learn first",
+        "Words learned",
+    ]
+    .iter()
+    .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let target = state.reading_target as f64 / 100.0;
+    let planned = state.reading_order.iter().enumerate().map(|(i, step)| {
+        let style = if step.coverage < target {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from((i + 1).to_string()),
+            Cell::from(step.title.clone()),
+            Cell::from(step.author.clone()),
+            Cell::from(format!("{:.3}", step.coverage)),
+            Cell::from(step.words_to_target.len().to_string()),
+            Cell::from(step.words_learned.to_string()),
+        ])
+        .style(style)
+    });
+    let excluded = state
+        .reading_order_books()
+        .into_iter()
+        .skip(state.reading_order.len())
+        .map(|(title, author)| {
+            Row::new(vec![
+                Cell::from("-"),
+                Cell::from(title.to_string()),
+                Cell::from(author.to_string()),
+                Cell::from("excluded"),
+                Cell::from(""),
+                Cell::from(""),
+            ])
+            .style(Style::default().fg(Color::DarkGray))
+        });
+    let below_target = state
+        .reading_order
+        .iter()
+        .filter(|step| step.coverage < target)
+        .count();
+    let title = format!(
+        "Reading order (learning words occurring >= {} times): {} of {} books below {}% known",
+        PLANNER_MIN_FREQUENCY,
+        below_target,
+        state.reading_order.len(),
+        state.reading_target
+    );
+    let table = Table::new(planned.chain(excluded).collect::<Vec<_>>())
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(selected_style)
+        .highlight_symbol(">> ")
+        .widths(&[
+            Constraint::Percentage(8),
+            Constraint::Percentage(28),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+            Constraint::Percentage(16),
+        ]);
+    frame.render_stateful_widget(table, chunks[0], &mut state.order_table_state.borrow_mut());
+
+    let (words_title, words) = match state.selected_step() {
+        Some(step) if !step.words_to_target.is_empty() => (
+            format!(
+                "Learn before reading {} to reach {}%",
+                step.title, state.reading_target
+            ),
+            step.words_to_target
+                .iter()
+                .map(|(word, freq)| format!("{} ({})", word, freq))
+                .collect::<Vec<_>>()
+                .join("  "),
+        ),
+        Some(step) => (
+            format!("{} is above {}%", step.title, state.reading_target),
+            String::new(),
+        ),
+        None => (
+            "Select a planned book to see the words to learn first".to_string(),
+            String::new(),
+        ),
+    };
+    let words_panel = Paragraph::new(words)
+        .block(Block::default().borders(Borders::ALL).title(words_title))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(words_panel, chunks[1]);
+}
+
+fn draw_library_words(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &DisplayState,
+    area: Rect,
+) {
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let header_cells = ["Word", "Books", "Frequency", "Dispersion"]
+        .iter()
+        .map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let rows = state.recommendations.iter().map(|w| {
+        let cells = vec![
+            Cell::from(w.word.clone()),
+            Cell::from(format!("{}/{}", w.books_containing, state.book_amount)),
+            Cell::from(w.frequency.to_string()),
+            Cell::from(format!("{:.2}", w.dispersion)),
+        ];
+        Row::new(cells)
+    });
+    let title = format!(
+        "Unknown words in >= {} of {} books ({} words total): {}",
+        state.min_books,
+        state.book_amount,
+        state.total_words,
+        state.recommendations.len()
+    );
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(selected_style)
+        .highlight_symbol(">> ")
+        .widths(&[
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ]);
+    frame.render_stateful_widget(table, area, &mut state.table_state.borrow_mut());
+}
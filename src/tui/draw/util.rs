@@ -15,7 +15,7 @@ use crate::analysis::AnalysisInfo;
 
 // ------- UTIL FUNCTIONS - DATA/UI ---------
 
-pub fn get_analysis_info_table(info: &AnalysisInfo, title: String) -> Table {
+pub fn get_analysis_info_table(info: &AnalysisInfo, title: String) -> Table<'_> {
     let row1 = Row::new(vec![
         "total words".to_string(),
         info.total_words.to_string(),
@@ -108,6 +108,39 @@ pub fn get_analysis_info_percentage_table<'c>(
         ])
 }
 
+/// Render values as a single line of block characters, merging neighbouring values
+/// into buckets if there are more values than width
+///
+/// scale: None to scale bars to the largest bucket (buckets sum up their values),
+/// Some(max) for a fixed scale from 0 to max (buckets average their values)
+pub fn get_sparkline_string(values: &[u64], width: usize, scale: Option<u64>) -> String {
+    const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let bucket_size = values.len().div_ceil(width);
+    let buckets: Vec<u64> = values
+        .chunks(bucket_size)
+        .map(|chunk| match scale {
+            Some(_) => chunk.iter().sum::<u64>() / chunk.len() as u64,
+            None => chunk.iter().sum(),
+        })
+        .collect();
+    let max = scale.unwrap_or_else(|| *buckets.iter().max().unwrap());
+    buckets
+        .iter()
+        .map(|v| {
+            if *v == 0 {
+                BARS[0]
+            } else {
+                // every non zero value gets at least the lowest bar
+                let level = (*v.min(&max) as f64 / max as f64 * 7.0).round() as usize + 1;
+                BARS[level.min(8)]
+            }
+        })
+        .collect()
+}
+
 pub fn draw_centered_input(
     frame: &mut Frame<CrosstermBackend<impl Write>>,
     area: Rect,
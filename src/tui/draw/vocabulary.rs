@@ -0,0 +1,161 @@
+use std::io::Write;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+use crate::history::format_date;
+use crate::tui::draw::util::{draw_centered_input, get_centered_rect};
+use crate::tui::state::vocabulary::{DisplayState, VocabularyInput};
+
+pub fn draw_vocabulary_display(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &DisplayState,
+    area: Rect,
+) {
+    let horizontal_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+        .split(area);
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(4)].as_ref())
+        .split(horizontal_chunks[0]);
+
+    let search_style = if matches!(state.input, Some(VocabularyInput::Search)) {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    let title = if state.show_ignored {
+        format!(
+            "Search ({} of {} ignored words)",
+            state.visible.len(),
+            state.ignored.len()
+        )
+    } else {
+        format!(
+            "Search ({} of {} words)",
+            state.visible.len(),
+            state.words.len()
+        )
+    };
+    let search = Paragraph::new(Span::styled(state.search.clone(), search_style))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(search, vertical_chunks[0]);
+
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let headers = if state.show_ignored {
+        ["Word", "Ignored since", "Source"]
+    } else {
+        ["Word", "Status", "Source"]
+    };
+    let header_cells = headers.iter().map(|h| Cell::from(*h).style(header_style));
+    let header = Row::new(header_cells).height(1).bottom_margin(1);
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let rows = state.visible.iter().map(|&i| {
+        if state.show_ignored {
+            let word = &state.ignored[i];
+            Row::new(vec![
+                Cell::from(word.word.clone()),
+                Cell::from(format_date(word.added_at)),
+                Cell::from(word.source.to_string()),
+            ])
+        } else {
+            let word = &state.words[i];
+            Row::new(vec![
+                Cell::from(word.word.clone()),
+                Cell::from(word.status.to_string()),
+                Cell::from(word.source()),
+            ])
+        }
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(selected_style)
+        .highlight_symbol(">> ")
+        .widths(&[
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+        ]);
+    frame.render_stateful_widget(
+        table,
+        vertical_chunks[1],
+        &mut state.table_state.borrow_mut(),
+    );
+
+    draw_word_details(frame, state, horizontal_chunks[1]);
+
+    let popup = match &state.input {
+        Some(VocabularyInput::Add(word)) => Some((word, "Word to add as known (external)")),
+        Some(VocabularyInput::MarkChars(true, chars)) => {
+            Some((chars, "Characters to mark as known"))
+        }
+        Some(VocabularyInput::MarkChars(false, chars)) => {
+            Some((chars, "Characters to mark as unknown"))
+        }
+        Some(VocabularyInput::UnmarkChars(chars)) => Some((chars, "Characters to unmark")),
+        _ => None,
+    };
+    if let Some((text, title)) = popup {
+        frame.render_widget(Clear, get_centered_rect(area));
+        draw_centered_input(frame, area, text, title);
+    }
+}
+
+// books containing the selected word and word lists that tagged it
+fn draw_word_details(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &DisplayState,
+    area: Rect,
+) {
+    let header_style = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![];
+    let title = match state.current_word() {
+        Some(word) => {
+            let books = state.books_containing(word);
+            lines.push(Spans::from(Span::styled(
+                format!("Books ({})", books.len()),
+                header_style,
+            )));
+            for (title, frequency) in books {
+                lines.push(Spans::from(format!("{} ({}x)", title, frequency)));
+            }
+            lines.push(Spans::from(""));
+            let word_lists = state.word_lists_tagging(word);
+            lines.push(Spans::from(Span::styled(
+                format!("Word lists ({})", word_lists.len()),
+                header_style,
+            )));
+            for (name, category) in word_lists {
+                lines.push(Spans::from(format!("{}: {:?}", name, category)));
+            }
+            let marked_chars = state.marked_chars(word);
+            if !marked_chars.is_empty() {
+                lines.push(Spans::from(""));
+                lines.push(Spans::from(Span::styled("Marked characters", header_style)));
+                for (hanzi, known) in marked_chars {
+                    let mark = if known { "known" } else { "unknown" };
+                    lines.push(Spans::from(format!("{}: {}", hanzi, mark)));
+                }
+            }
+            word.to_string()
+        }
+        None => "No word selected".to_string(),
+    };
+    let details = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: true });
+    frame.render_widget(details, area);
+}
@@ -19,7 +19,7 @@ pub fn draw_word_lists(
     let header_style = Style::default()
         .fg(Color::Yellow)
         .add_modifier(Modifier::BOLD);
-    let header_cells = ["Book", "Author", "#w", "#c"]
+    let header_cells = ["Book", "Author", "#w", "#c", "Coverage", "字", "Filter"]
         .iter()
         .map(|h| Cell::from(*h).style(header_style));
     let header = Row::new(header_cells).height(1).bottom_margin(1);
@@ -34,6 +34,15 @@ pub fn draw_word_lists(
                 "{:?}",
                 wl.analysis_query.min_occurrence_unknown_chars
             )),
+            Cell::from(match wl.coverage_target {
+                Some(target) => format!("{}%", target),
+                None => "-".to_string(),
+            }),
+            Cell::from(if wl.character_focused { "✓" } else { "" }),
+            Cell::from(match &wl.analysis_query.filter {
+                Some(filter) => filter.source().to_string(),
+                None => "-".to_string(),
+            }),
         ];
         Row::new(cells)
     });
@@ -43,10 +52,13 @@ pub fn draw_word_lists(
         .highlight_style(selected_style)
         .highlight_symbol(">> ")
         .widths(&[
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
+            Constraint::Percentage(20),
+            Constraint::Percentage(15),
+            Constraint::Percentage(8),
+            Constraint::Percentage(8),
+            Constraint::Percentage(10),
+            Constraint::Percentage(5),
+            Constraint::Percentage(34),
         ]);
     frame.render_stateful_widget(table, area, &mut state.table_state.borrow_mut());
 }
@@ -96,7 +108,7 @@ pub fn draw_opened_word_list(
     frame.render_stateful_widget(table, chunks[1], &mut state.table_state.borrow_mut());
 }
 
-fn get_summary(state: &WordListSummary) -> Paragraph {
+fn get_summary(state: &WordListSummary) -> Paragraph<'_> {
     Paragraph::new(Span::from(format!(
         "{}/{} filtered | learn: {}, not learn: {}, ignore: {}",
         state.filtered, state.total, state.to_learn, state.to_not_learn, state.to_ignore
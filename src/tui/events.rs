@@ -1,6 +1,8 @@
 mod analysis;
 mod books;
 mod info;
+mod library;
+mod vocabulary;
 mod word_list;
 
 use anyhow::Result;
@@ -9,15 +11,20 @@ use crossterm::event::KeyCode;
 use event::KeyEvent;
 
 use self::analysis::handle_event_analysis;
+use self::books::handle_event_books_comparing;
 use self::books::handle_event_books_display;
 use self::books::handle_event_books_enter_to_import;
 use self::info::handle_event_info;
+use self::library::handle_event_library;
+use self::vocabulary::handle_event_vocabulary;
 use self::word_list::handle_event_word_list_opened;
 use self::word_list::handle_event_word_lists;
 
 use super::state::analysis::AnalysisState;
 use super::state::books::BooksState;
 use super::state::info::InfoState;
+use super::state::library::LibraryState;
+use super::state::vocabulary::VocabularyState;
 use super::state::word_list::WordListState;
 use super::state::TuiState;
 use super::state::View;
@@ -57,6 +64,25 @@ pub(super) fn handle_event(mut state: TuiState, event: Event<KeyEvent>) -> Resul
                         }
                         return Ok(state);
                     }
+                    KeyCode::Char('4') => {
+                        state.current_view = View::Library;
+                        if matches!(state.library_state, LibraryState::Uninitialized) {
+                            state.library_state = LibraryState::init(state.db_connection.clone())?;
+                        }
+                        return Ok(state);
+                    }
+                    KeyCode::Char('5') => {
+                        state.current_view = View::Vocabulary;
+                        match &mut state.vocabulary_state {
+                            VocabularyState::Uninitialized => {
+                                state.vocabulary_state =
+                                    VocabularyState::init(state.db_connection.clone())?;
+                            }
+                            // books, word lists and words may have changed in other tabs
+                            VocabularyState::Display(display_state) => display_state.reload()?,
+                        }
+                        return Ok(state);
+                    }
                     _ => {}
                 }
             }
@@ -102,6 +128,9 @@ pub(super) fn handle_event(mut state: TuiState, event: Event<KeyEvent>) -> Resul
                 }
                 _ => {}
             }
+            if let LibraryState::Calculating(calculating_state) = &state.library_state {
+                state.library_state = calculating_state.update();
+            }
             return Ok(state);
         }
     };
@@ -125,12 +154,20 @@ pub(super) fn handle_event(mut state: TuiState, event: Event<KeyEvent>) -> Resul
             let new_state = match &state.info_state {
                 // allow no tab specific actions during syncing
                 InfoState::Syncing(_) => None,
-                InfoState::Display(display_state) => {
-                    handle_event_info(&state, &display_state.vocab_info, key_event)
-                }
+                InfoState::Display(display_state) => handle_event_info(
+                    &state,
+                    &display_state.vocab_info,
+                    &display_state.history,
+                    key_event,
+                ),
                 InfoState::SyncError(sync_error_state) => {
                     // switch back to display state
-                    handle_event_info(&state, &sync_error_state.previous_vocab_info, key_event)
+                    handle_event_info(
+                        &state,
+                        &sync_error_state.previous_vocab_info,
+                        &sync_error_state.previous_history,
+                        key_event,
+                    )
                 }
             };
             // switched to syncing state
@@ -167,7 +204,7 @@ pub(super) fn handle_event(mut state: TuiState, event: Event<KeyEvent>) -> Resul
                         display_state,
                         key_event,
                         state.db_connection.clone(),
-                    );
+                    )?;
                     update_action_log(&mut state.action_log, action);
                     if let Some(analysis_state) = analysis_state {
                         state.analysis_state = analysis_state;
@@ -184,6 +221,38 @@ pub(super) fn handle_event(mut state: TuiState, event: Event<KeyEvent>) -> Resul
                     update_action_log(&mut state.action_log, action);
                     new_state
                 }
+                BooksState::Comparing(comparing_state) => {
+                    handle_event_books_comparing(comparing_state, key_event)
+                }
+                x => x,
+            }
+        }
+        View::Library => {
+            state.library_state = match state.library_state {
+                LibraryState::Display(display_state) => {
+                    let (new_state, action) = handle_event_library(
+                        display_state,
+                        key_event,
+                        state.db_connection.clone(),
+                    )?;
+                    update_action_log(&mut state.action_log, action);
+                    new_state
+                }
+                x => x,
+            }
+        }
+        View::Vocabulary => {
+            state.vocabulary_state = match state.vocabulary_state {
+                VocabularyState::Display(display_state) => {
+                    let (new_state, action, changed) =
+                        handle_event_vocabulary(display_state, key_event)?;
+                    update_action_log(&mut state.action_log, action);
+                    // vocabulary stats and history include the changed word
+                    if changed && matches!(state.info_state, InfoState::Display(_)) {
+                        state.info_state = InfoState::init(state.db_connection.clone())?;
+                    }
+                    new_state
+                }
                 x => x,
             }
         }
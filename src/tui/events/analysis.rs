@@ -1,17 +1,25 @@
 use crate::{
-    analysis::get_filtered_extraction_items,
+    analysis::{get_filtered_extraction_items, AnalysisQuery},
+    config::get_config,
+    db::vocab::{db_words_select_known, describe_known_levels},
     db::word_lists::db_wlist_insert,
     extraction::ExtractionItem,
-    tui::state::analysis::{AnalysisState, ExtractedState},
-    word_lists::construct_word_list,
+    filter::FilterExpr,
+    grammar::grammar_report,
+    levels::MAX_LEVEL,
+    report::{build_report, default_report_queries, render_report, ReportFormat},
+    tui::state::analysis::{next_known_levels, AnalysisSection, AnalysisState, ExtractedState},
+    vocabulary::get_known_words_and_chars,
+    word_lists::{construct_word_list, WordListMetadata},
 };
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use crossterm::event;
 use crossterm::event::KeyCode;
 use event::KeyEvent;
 use rusqlite::Connection;
 use std::{
     collections::HashSet,
+    fs,
     sync::{Arc, Mutex},
 };
 
@@ -20,30 +28,203 @@ pub fn handle_event_analysis(
     key_event: KeyEvent,
     db: Arc<Mutex<Connection>>,
 ) -> Result<(AnalysisState, Option<String>)> {
-    let mut analysis_query = extracted_state.analysis_query;
+    if let Some(filter_input) = extracted_state.filter_input.take() {
+        return handle_event_filter_input(extracted_state, filter_input, key_event);
+    }
+    let mut analysis_query = extracted_state.analysis_query.clone();
     let mut action_log_entry: Option<String> = None;
     match key_event.code {
+        // enter filter expression, starting from the current one
+        KeyCode::Char('f') => {
+            extracted_state.filter_input = Some(
+                analysis_query
+                    .filter
+                    .as_ref()
+                    .map(|filter| filter.source().to_string())
+                    .unwrap_or_default(),
+            );
+        }
         KeyCode::Char('r') => return Ok((AnalysisState::Blank, None)),
         KeyCode::Char('s') => {
             let book = &extracted_state.extraction_result.segmented_book;
             let title = &extracted_state.book_title;
             let author = &extracted_state.book_author;
-            let analysis_query = extracted_state.analysis_query;
+            let analysis_query = extracted_state.analysis_query.clone();
             let unknown_words_to_save: HashSet<&ExtractionItem> = get_filtered_extraction_items(
                 &extracted_state.extraction_result,
-                analysis_query.min_occurrence_words,
-                &extracted_state.known_words_and_chars,
-                analysis_query.min_occurrence_unknown_chars,
+                &analysis_query,
+                &extracted_state.vocabulary.known_words_and_chars,
+                &extracted_state.vocabulary.char_overrides,
             )
             .into_iter()
-            .filter(|item| !extracted_state.known_words_and_chars.contains(&item.word))
+            .filter(|item| {
+                !extracted_state
+                    .vocabulary
+                    .known_words_and_chars
+                    .contains(&item.word)
+            })
             .collect();
-            let word_list =
-                construct_word_list(book, title, author, analysis_query, &unknown_words_to_save);
+            let word_list = construct_word_list(
+                book,
+                WordListMetadata::new(title, author, analysis_query),
+                &unknown_words_to_save,
+                &extracted_state.vocabulary.known_words_and_chars,
+                &extracted_state.vocabulary.ignored_words,
+            );
             db_wlist_insert(&db.lock().unwrap(), word_list)
                 .context("unable to save word list to DB")?;
             action_log_entry = Some(format!("Saved word list for {}", book.title));
         }
+        // save words of coverage plan as word list
+        KeyCode::Char('p') => {
+            let plan_words: HashSet<&str> = extracted_state
+                .coverage_plan
+                .iter()
+                .map(|step| step.word.as_str())
+                .collect();
+            let words_to_save: HashSet<&ExtractionItem> = extracted_state
+                .extraction_result
+                .vocabulary
+                .iter()
+                .filter(|item| plan_words.contains(item.word.as_str()))
+                .collect();
+            // plan considers all words of the book
+            let query_all = AnalysisQuery::new(1);
+            let mut metadata = WordListMetadata::new(
+                &extracted_state.book_title,
+                &extracted_state.book_author,
+                query_all,
+            );
+            metadata.coverage_target = Some(extracted_state.coverage_target);
+            let word_list = construct_word_list(
+                &extracted_state.extraction_result.segmented_book,
+                metadata,
+                &words_to_save,
+                &extracted_state.vocabulary.known_words_and_chars,
+                &extracted_state.vocabulary.ignored_words,
+            );
+            db_wlist_insert(&db.lock().unwrap(), word_list)
+                .context("unable to save word list to DB")?;
+            action_log_entry = Some(format!(
+                "Saved {}% coverage word list for {}",
+                extracted_state.coverage_target, extracted_state.book_title
+            ));
+        }
+        // save most frequent word of each unknown character as word list
+        KeyCode::Char('c') => {
+            let char_words: HashSet<&str> = extracted_state
+                .unknown_chars
+                .iter()
+                .filter_map(|c| c.words.first())
+                .map(|(word, _)| word.as_str())
+                .collect();
+            let words_to_save: HashSet<&ExtractionItem> = extracted_state
+                .extraction_result
+                .vocabulary
+                .iter()
+                .filter(|item| char_words.contains(item.word.as_str()))
+                .collect();
+            let query_all = AnalysisQuery::new(1);
+            let mut metadata = WordListMetadata::new(
+                &extracted_state.book_title,
+                &extracted_state.book_author,
+                query_all,
+            );
+            metadata.character_focused = true;
+            let word_list = construct_word_list(
+                &extracted_state.extraction_result.segmented_book,
+                metadata,
+                &words_to_save,
+                &extracted_state.vocabulary.known_words_and_chars,
+                &extracted_state.vocabulary.ignored_words,
+            );
+            db_wlist_insert(&db.lock().unwrap(), word_list)
+                .context("unable to save word list to DB")?;
+            action_log_entry = Some(format!(
+                "Saved character word list for {}",
+                extracted_state.book_title
+            ));
+        }
+        // reduce max level, starting from highest level if there is none
+        KeyCode::Char('<') => {
+            analysis_query.max_level = match analysis_query.max_level {
+                Some(level) => Some(level.saturating_sub(1).max(1)),
+                None => Some(MAX_LEVEL),
+            };
+        }
+        // increase max level, removing level restriction after highest level
+        KeyCode::Char('>') => {
+            analysis_query.max_level = match analysis_query.max_level {
+                Some(level) if level < MAX_LEVEL => Some(level + 1),
+                _ => None,
+            };
+        }
+        // adjust targeted coverage
+        KeyCode::Char('+') => {
+            let target = extracted_state.coverage_target + 1;
+            extracted_state.set_coverage_target(target);
+        }
+        KeyCode::Char('-') => {
+            let target = extracted_state.coverage_target - 1;
+            extracted_state.set_coverage_target(target);
+        }
+        // export grammar patterns with example sentences
+        KeyCode::Char('g') => {
+            let filename = format!("{}-grammar.txt", extracted_state.book_title);
+            let mut p = get_config().export_base_path;
+            p.push(&filename);
+            let report = grammar_report(
+                &extracted_state.chapter_titles(),
+                &extracted_state.grammar_occurrences,
+            );
+            fs::write(&p, report).context("could not write grammar report")?;
+            action_log_entry = Some(format!("{} exported", &filename));
+        }
+        // export report of the current query in all formats
+        KeyCode::Char('e') => {
+            let report = build_report(
+                &extracted_state.book_title,
+                &extracted_state.book_author,
+                &extracted_state.extraction_result,
+                &extracted_state.vocabulary,
+                &default_report_queries(),
+                &extracted_state.analysis_query,
+            );
+            let mut filenames = vec![];
+            for format in ReportFormat::ALL {
+                let filename = format!(
+                    "{}-report.{}",
+                    extracted_state.book_title,
+                    format.extension()
+                );
+                let mut p = get_config().export_base_path;
+                p.push(&filename);
+                fs::write(&p, render_report(&report, format)?).context("could not write report")?;
+                filenames.push(filename);
+            }
+            action_log_entry = Some(format!("{} exported", filenames.join(", ")));
+        }
+        KeyCode::Tab => {
+            extracted_state.section = extracted_state.section.next();
+        }
+        // select previous/next grammar pattern to show its chapters
+        KeyCode::Char('[') if extracted_state.section == AnalysisSection::Grammar => {
+            let index = extracted_state.selected_grammar.saturating_sub(1);
+            extracted_state.set_selected_grammar(index);
+        }
+        KeyCode::Char(']') if extracted_state.section == AnalysisSection::Grammar => {
+            let index = extracted_state.selected_grammar + 1;
+            extracted_state.set_selected_grammar(index);
+        }
+        // move reading position to previous/next chapter
+        KeyCode::Char('[') => {
+            let position = extracted_state.reading_position.saturating_sub(1);
+            extracted_state.set_reading_position(position);
+        }
+        KeyCode::Char(']') => {
+            let position = extracted_state.reading_position + 1;
+            extracted_state.set_reading_position(position);
+        }
         // reduce min_occurrence of words
         KeyCode::Char('j') => {
             analysis_query.min_occurrence_words = *analysis_query
@@ -72,13 +253,82 @@ pub fn handle_event_analysis(
         // increase min_occurrence of unknown chars
         KeyCode::Char('l') => {
             analysis_query.min_occurrence_unknown_chars =
-                match extracted_state.analysis_query.min_occurrence_unknown_chars {
+                match analysis_query.min_occurrence_unknown_chars {
                     Some(amount) => Some(amount + 1),
                     None => Some(1),
                 }
         }
+        // change which Anki levels count as known
+        KeyCode::Char('v') => {
+            let known_levels = next_known_levels(&extracted_state.known_levels);
+            let known_words = db_words_select_known(&db.lock().unwrap(), &known_levels)?;
+            action_log_entry = Some(format!("known: {}", describe_known_levels(&known_levels)));
+            let known_words_and_chars =
+                get_known_words_and_chars(known_words, &extracted_state.vocabulary.char_overrides);
+            extracted_state.set_known(known_levels, known_words_and_chars);
+        }
+        // toggle dropping of unknown words made of known characters
+        KeyCode::Char('x') => {
+            analysis_query.exclude_guessable = !analysis_query.exclude_guessable;
+        }
         _ => {}
     }
     extracted_state.query_update(analysis_query);
     Ok((AnalysisState::Extracted(extracted_state), action_log_entry))
 }
+
+fn handle_event_filter_input(
+    mut extracted_state: Box<ExtractedState>,
+    mut filter_input: String,
+    key_event: KeyEvent,
+) -> Result<(AnalysisState, Option<String>)> {
+    let mut action_log_entry = None;
+    match key_event.code {
+        KeyCode::Char(c) => {
+            filter_input.push(c);
+            extracted_state.filter_input = Some(filter_input);
+        }
+        KeyCode::Backspace => {
+            filter_input.pop();
+            extracted_state.filter_input = Some(filter_input);
+        }
+        KeyCode::Esc => {}
+        KeyCode::Enter => match parse_filter_input(&filter_input) {
+            Ok(filter) => {
+                let mut analysis_query = extracted_state.analysis_query.clone();
+                action_log_entry = Some(match &filter {
+                    Some(filter) => format!("filter: {}", filter.source()),
+                    None => "filter removed".to_string(),
+                });
+                analysis_query.filter = filter;
+                extracted_state.query_update(analysis_query);
+            }
+            Err(e) => {
+                action_log_entry = Some(format!("invalid filter: {}", e));
+                extracted_state.filter_input = Some(filter_input);
+            }
+        },
+        _ => {
+            extracted_state.filter_input = Some(filter_input);
+        }
+    }
+    Ok((AnalysisState::Extracted(extracted_state), action_log_entry))
+}
+
+// empty input removes the filter, @name refers to a preset of the config
+fn parse_filter_input(filter_input: &str) -> Result<Option<FilterExpr>> {
+    let filter_input = filter_input.trim();
+    if filter_input.is_empty() {
+        return Ok(None);
+    }
+    match filter_input.strip_prefix('@') {
+        Some(name) => {
+            let presets = get_config().filter_presets;
+            let preset = presets
+                .get(name)
+                .ok_or_else(|| anyhow!("no filter preset named {}", name))?;
+            FilterExpr::parse(preset).map(Some)
+        }
+        None => FilterExpr::parse(filter_input).map(Some),
+    }
+}
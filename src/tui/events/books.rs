@@ -1,38 +1,66 @@
 use std::sync::{Arc, Mutex};
 
+use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use rusqlite::Connection;
 
 use crate::{
-    db::books::db_books_delete,
+    config::get_config,
+    db::{
+        books::db_books_delete, chars::db_char_overrides_select, ignore::db_ignored_select_words,
+        levels::db_levels_select_all,
+    },
     ebook::open_as_flat_book,
     extraction::extract_vocab_from_segmented,
+    levels::annotate_levels,
     tui::state::{
-        analysis::{AnalysisState, ExtractedState},
-        books::{BooksState, DisplayState, ImportingState},
+        analysis::{AnalysisState, ExtractedState, QueryCache},
+        books::{BooksState, ComparingState, DisplayState, ImportingState},
     },
+    vocabulary::VocabularyContext,
 };
 
 pub fn handle_event_books_display(
     mut state: DisplayState,
     key_event: KeyEvent,
     db: Arc<Mutex<Connection>>,
-) -> (BooksState, Option<AnalysisState>, Option<String>) {
-    match key_event.code {
+) -> Result<(BooksState, Option<AnalysisState>, Option<String>)> {
+    let res = match key_event.code {
         KeyCode::Char('i') => (BooksState::EnterToImport("".to_string()), None, None),
         KeyCode::Enter => {
             let (mut analysis_state, mut action) = (None, None);
             if let (Some(book), known_words_and_chars) =
                 (state.get_current(), state.known_words_and_chars.clone())
             {
-                let extraction_result = extract_vocab_from_segmented(book.book.clone());
+                let mut extraction_result = extract_vocab_from_segmented(book.book.clone());
+                action = match db_levels_select_all(&db.lock().unwrap()) {
+                    Ok(levels) => {
+                        annotate_levels(&mut extraction_result, &levels);
+                        Some(format!("open {} for analysis", book.title))
+                    }
+                    Err(e) => Some(format!(
+                        "open {} for analysis without levels, err: {:?}",
+                        book.title, e
+                    )),
+                };
+                let vocabulary = VocabularyContext {
+                    known_words_and_chars,
+                    char_overrides: db_char_overrides_select(&db.lock().unwrap())?,
+                    ignored_words: db_ignored_select_words(&db.lock().unwrap())?,
+                    exclude_ignored: get_config().ignore.exclude_from_counts,
+                };
                 analysis_state = Some(AnalysisState::Extracted(Box::new(ExtractedState::new(
                     extraction_result,
-                    known_words_and_chars,
+                    get_config().anki_levels.known,
+                    vocabulary,
                     &book.title,
                     &book.author,
+                    QueryCache {
+                        db_connection: db.clone(),
+                        book_hash: book.book_hash.clone(),
+                        vocab_version: state.vocab_version,
+                    },
                 ))));
-                action = Some(format!("open {} for analysis", book.title));
             };
             (BooksState::Display(state), analysis_state, action)
         }
@@ -44,18 +72,42 @@ pub fn handle_event_books_display(
             state.select_previous();
             (BooksState::Display(state), None, None)
         }
+        KeyCode::Char('m') => {
+            state.toggle_mark();
+            (BooksState::Display(state), None, None)
+        }
+        KeyCode::Char('c') => match state.marked_and_current() {
+            Some((a, b)) => {
+                let comparing_state = ComparingState::new(state, a, b);
+                let action = Some(format!(
+                    "compare {} with {}",
+                    comparing_state.title_a, comparing_state.title_b
+                ));
+                (
+                    BooksState::Comparing(Box::new(comparing_state)),
+                    None,
+                    action,
+                )
+            }
+            None => (
+                BooksState::Display(state),
+                None,
+                Some("mark a book [M] and select another one to compare".to_string()),
+            ),
+        },
         KeyCode::Char('d') => {
             if let Some(book) = state.remove_current() {
                 let action = match db_books_delete(&db.lock().unwrap(), &book.title, &book.author) {
                     Ok(_) => Some(format!("deleted {}", book.title)),
                     Err(e) => Some(format!("deletion failed, err: {:?}", e)),
                 };
-                return (BooksState::Display(state), None, action);
+                return Ok((BooksState::Display(state), None, action));
             }
             (BooksState::Display(state), None, None)
         }
         _ => (BooksState::Display(state), None, None),
-    }
+    };
+    Ok(res)
 }
 
 pub fn handle_event_books_enter_to_import(
@@ -89,3 +141,16 @@ pub fn handle_event_books_enter_to_import(
     }
     (BooksState::EnterToImport(partial_path), None)
 }
+
+pub fn handle_event_books_comparing(
+    mut state: Box<ComparingState>,
+    key_event: KeyEvent,
+) -> BooksState {
+    match key_event.code {
+        KeyCode::Esc => return BooksState::Display(state.display_state),
+        KeyCode::Char('j') => state.select_next(),
+        KeyCode::Char('k') => state.select_previous(),
+        _ => {}
+    }
+    BooksState::Comparing(state)
+}
@@ -1,3 +1,4 @@
+use crate::history::VocabSnapshot;
 use crate::tui::state::info::{InfoState, SyncingState};
 use crate::tui::state::TuiState;
 use crate::vocabulary::VocabularyInfo;
@@ -9,11 +10,13 @@ use event::KeyEvent;
 pub fn handle_event_info(
     state: &TuiState,
     current_vocab_info: &VocabularyInfo,
+    current_history: &[VocabSnapshot],
     key_event: KeyEvent,
 ) -> Option<InfoState> {
     match key_event.code {
         KeyCode::Char('s') => Some(InfoState::Syncing(SyncingState::new(
             *current_vocab_info,
+            current_history.to_vec(),
             state.db_connection.clone(),
         ))),
         _ => None,
@@ -0,0 +1,67 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent};
+use rusqlite::Connection;
+
+use crate::config::get_config;
+use crate::corpus::corpus_report;
+use crate::db::word_lists::db_wlist_insert;
+use crate::tui::state::library::{DisplayState, LibrarySection, LibraryState};
+use crate::word_lists::construct_library_word_list;
+
+pub fn handle_event_library(
+    mut state: Box<DisplayState>,
+    key_event: KeyEvent,
+    db: Arc<Mutex<Connection>>,
+) -> Result<(LibraryState, Option<String>)> {
+    let mut action = None;
+    match key_event.code {
+        KeyCode::Char('j') => {
+            state.select_next();
+        }
+        KeyCode::Char('k') => {
+            state.select_previous();
+        }
+        KeyCode::Tab => {
+            state.toggle_section();
+        }
+        // adjust targeted share of known words
+        KeyCode::Char('+') => {
+            let target = state.reading_target + 1;
+            state.set_reading_target(target);
+        }
+        KeyCode::Char('-') => {
+            let target = state.reading_target - 1;
+            state.set_reading_target(target);
+        }
+        // include/exclude selected book from reading order
+        KeyCode::Char('x') if state.section == LibrarySection::ReadingOrder => {
+            state.toggle_selected_book();
+        }
+        // recompute with current books and vocabulary
+        KeyCode::Char('r') => {
+            return LibraryState::init(db).map(|state| (state, None));
+        }
+        // save recommendations as word list that is not tied to a book
+        KeyCode::Char('s') if state.section == LibrarySection::Words => {
+            let word_list = construct_library_word_list(&state.recommendations);
+            db_wlist_insert(&db.lock().unwrap(), word_list)?;
+            action = Some(format!(
+                "saved library word list ({} words)",
+                state.recommendations.len()
+            ));
+        }
+        KeyCode::Char('e') if state.section == LibrarySection::Words => {
+            let filename = "library-words.tsv";
+            let mut p = get_config().export_base_path;
+            p.push(filename);
+            fs::write(&p, corpus_report(&state.recommendations))
+                .context("could not write library words")?;
+            action = Some(format!("{} exported", filename));
+        }
+        _ => {}
+    }
+    Ok((LibraryState::Display(state), action))
+}
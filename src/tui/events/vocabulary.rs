@@ -0,0 +1,125 @@
+use anyhow::Result;
+use crossterm::event;
+use crossterm::event::KeyCode;
+use event::KeyEvent;
+
+use crate::tui::state::vocabulary::{DisplayState, VocabularyInput, VocabularyState};
+
+/// Handle key event of vocabulary view, also returns whether the vocabulary changed
+pub fn handle_event_vocabulary(
+    mut state: Box<DisplayState>,
+    key_event: KeyEvent,
+) -> Result<(VocabularyState, Option<String>, bool)> {
+    if let Some(input) = state.input.take() {
+        return handle_event_vocabulary_input(state, input, key_event);
+    }
+    let mut action = None;
+    let mut changed = false;
+    match key_event.code {
+        KeyCode::Char('j') => state.select_next(),
+        KeyCode::Char('k') => state.select_previous(),
+        KeyCode::Char('/') => state.input = Some(VocabularyInput::Search),
+        KeyCode::Char('a') => state.input = Some(VocabularyInput::Add(String::new())),
+        KeyCode::Char('c') => state.input = Some(VocabularyInput::MarkChars(true, String::new())),
+        KeyCode::Char('u') => state.input = Some(VocabularyInput::MarkChars(false, String::new())),
+        KeyCode::Char('r') => state.input = Some(VocabularyInput::UnmarkChars(String::new())),
+        KeyCode::Char('g') => state.toggle_ignored(),
+        KeyCode::Char('d') if state.show_ignored => {
+            if let Some(word) = state.remove_current_ignored()? {
+                action = Some(format!("no longer ignoring {}", word));
+            }
+        }
+        KeyCode::Char('d') => {
+            action = Some(match state.delete_current_external()? {
+                Some(word) => {
+                    changed = true;
+                    format!("deleted external word {}", word)
+                }
+                None => "only added external words can be deleted".to_string(),
+            });
+        }
+        // clear search
+        KeyCode::Esc => state.set_search(String::new()),
+        _ => {}
+    }
+    Ok((VocabularyState::Display(state), action, changed))
+}
+
+fn handle_event_vocabulary_input(
+    mut state: Box<DisplayState>,
+    input: VocabularyInput,
+    key_event: KeyEvent,
+) -> Result<(VocabularyState, Option<String>, bool)> {
+    let mut action = None;
+    let mut changed = false;
+    match input {
+        // search is applied while typing, Enter keeps it and Esc clears it
+        VocabularyInput::Search => match key_event.code {
+            KeyCode::Char(c) => {
+                let mut search = state.search.clone();
+                search.push(c);
+                state.set_search(search);
+                state.input = Some(VocabularyInput::Search);
+            }
+            KeyCode::Backspace => {
+                let mut search = state.search.clone();
+                search.pop();
+                state.set_search(search);
+                state.input = Some(VocabularyInput::Search);
+            }
+            KeyCode::Esc => state.set_search(String::new()),
+            KeyCode::Enter => {}
+            _ => state.input = Some(VocabularyInput::Search),
+        },
+        mut input => match key_event.code {
+            KeyCode::Char(c) => {
+                if let Some(text) = input.text_mut() {
+                    text.push(c);
+                }
+                state.input = Some(input);
+            }
+            KeyCode::Backspace => {
+                if let Some(text) = input.text_mut() {
+                    text.pop();
+                }
+                state.input = Some(input);
+            }
+            KeyCode::Esc => {}
+            KeyCode::Enter => (action, changed) = submit_input(&mut state, input)?,
+            _ => state.input = Some(input),
+        },
+    }
+    Ok((VocabularyState::Display(state), action, changed))
+}
+
+// apply entered text, returns the action and whether the vocabulary changed
+fn submit_input(
+    state: &mut DisplayState,
+    input: VocabularyInput,
+) -> Result<(Option<String>, bool)> {
+    let (text, known) = match input {
+        VocabularyInput::Search => return Ok((None, false)),
+        VocabularyInput::Add(word) => {
+            let word = word.trim();
+            if word.is_empty() {
+                return Ok((None, false));
+            }
+            let added = state.add_external(word)?;
+            let action = if added {
+                format!("added {} as external word", word)
+            } else {
+                format!("{} was already added", word)
+            };
+            return Ok((Some(action), added));
+        }
+        VocabularyInput::MarkChars(known, text) => (text, Some(known)),
+        VocabularyInput::UnmarkChars(text) => (text, None),
+    };
+    let changed = state.mark_chars(&text, known)?;
+    let action = match known {
+        Some(true) => format!("marked {} characters as known", changed.len()),
+        Some(false) => format!("marked {} characters as unknown", changed.len()),
+        None => format!("unmarked {} characters", changed.len()),
+    };
+    Ok((Some(action), !changed.is_empty()))
+}
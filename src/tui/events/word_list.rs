@@ -1,7 +1,10 @@
+use std::collections::HashSet;
 use std::fs;
 use std::sync::{Arc, Mutex};
 
 use crate::config::get_config;
+use crate::db::ignore::{db_ignored_add, db_ignored_del};
+use crate::db::vocab::{db_words_external_add, ExternalSource};
 use crate::db::word_lists::{db_wlist_delete_by_id, db_wlist_update};
 use crate::tui::state::word_list::{ListOfWordLists, OpenedWordList, WordListState};
 use crate::word_lists::tag_words;
@@ -43,7 +46,7 @@ pub fn handle_event_word_lists(
 
 pub fn handle_event_word_list_opened(
     key_event: KeyEvent,
-    mut state: OpenedWordList,
+    mut state: Box<OpenedWordList>,
     db: Arc<Mutex<Connection>>,
 ) -> Result<(WordListState, Option<String>)> {
     let mut action = None;
@@ -57,6 +60,42 @@ pub fn handle_event_word_list_opened(
                     state.word_list_id(),
                     &state.get_chapter_words(),
                 )?;
+                if get_config().ignore.from_tags {
+                    action = ignore_tagged_words(&state, &mut db.lock().unwrap())?;
+                }
+            }
+        }
+        // add words tagged as ignore in selected chapter to the global ignore list
+        KeyCode::Char('i') => {
+            action = ignore_tagged_words(&state, &mut db.lock().unwrap())?;
+        }
+        // remove words tagged as ignore in selected chapter from the global ignore list
+        KeyCode::Char('u') => {
+            if let Some((_, chapter_info)) = state.get_selected() {
+                let words: HashSet<String> = chapter_info
+                    .get_words_to_ignore()
+                    .iter()
+                    .map(|tw| tw.word.clone())
+                    .collect();
+                let deleted = db_ignored_del(
+                    &mut db.lock().unwrap(),
+                    &words,
+                    get_config().ignore.exclude_from_counts,
+                )?;
+                action = Some(format!("no longer ignoring {} words", deleted.len()));
+            }
+        }
+        // add words tagged as not to learn in selected chapter to the known external words
+        KeyCode::Char('m') => {
+            if let Some((_, chapter_info)) = state.get_selected() {
+                let words: HashSet<&str> = chapter_info
+                    .get_words_to_not_learn()
+                    .iter()
+                    .map(|tw| tw.word.as_str())
+                    .collect();
+                let source = ExternalSource::WordList(state.word_list_metadata().to_string());
+                let added = db_words_external_add(&mut db.lock().unwrap(), &words, &source, None)?;
+                action = Some(format!("marked {} new words as known", added.len()));
             }
         }
         KeyCode::Esc => {
@@ -70,10 +109,18 @@ pub fn handle_event_word_list_opened(
         }
         KeyCode::Char('e') => {
             if let Some((i, chapter_info)) = state.get_selected() {
-                let words_to_learn = chapter_info
-                    .get_words_to_learn()
-                    .iter()
-                    .fold("".to_string(), |s, w| format!("{}{}\n", s, w));
+                // one word per line, followed by its example sentences if there are any
+                let words_to_learn =
+                    chapter_info
+                        .get_words_to_learn()
+                        .iter()
+                        .fold("".to_string(), |s, tw| {
+                            if tw.examples.is_empty() {
+                                format!("{}{}\n", s, tw.word)
+                            } else {
+                                format!("{}{}\t{}\n", s, tw.word, tw.examples.join(" / "))
+                            }
+                        });
                 let chapter_title = chapter_info.chapter_title();
                 let wlist_metadata = state.word_list_metadata();
                 let mut p = get_config().export_base_path;
@@ -91,3 +138,17 @@ pub fn handle_event_word_list_opened(
     }
     Ok((WordListState::Opened(state), action))
 }
+
+fn ignore_tagged_words(state: &OpenedWordList, db: &mut Connection) -> Result<Option<String>> {
+    let Some((_, chapter_info)) = state.get_selected() else {
+        return Ok(None);
+    };
+    let words: HashSet<&str> = chapter_info
+        .get_words_to_ignore()
+        .iter()
+        .map(|tw| tw.word.as_str())
+        .collect();
+    let source = ExternalSource::WordList(state.word_list_metadata().to_string());
+    let added = db_ignored_add(db, &words, &source, get_config().ignore.exclude_from_counts)?;
+    Ok(Some(format!("ignoring {} new words", added.len())))
+}
@@ -1,6 +1,8 @@
 pub mod analysis;
 pub mod books;
 pub mod info;
+pub mod library;
+pub mod vocabulary;
 pub mod word_list;
 
 use anyhow::Result;
@@ -8,12 +10,17 @@ use std::sync::{Arc, Mutex};
 
 use rusqlite::Connection;
 
-use self::{analysis::AnalysisState, books::BooksState, info::InfoState, word_list::WordListState};
+use self::{
+    analysis::AnalysisState, books::BooksState, info::InfoState, library::LibraryState,
+    vocabulary::VocabularyState, word_list::WordListState,
+};
 
 pub struct TuiState {
     pub analysis_state: AnalysisState,
     pub books_state: BooksState,
     pub info_state: InfoState,
+    pub library_state: LibraryState,
+    pub vocabulary_state: VocabularyState,
     pub word_list_state: WordListState,
     pub current_view: View,
     pub db_connection: Arc<Mutex<Connection>>,
@@ -28,6 +35,10 @@ impl TuiState {
             books_state: BooksState::init(db_connection.clone())?,
             info_state: InfoState::init(db_connection.clone())?,
             word_list_state: WordListState::init(db_connection.clone())?,
+            // computed when the library view is first opened
+            library_state: LibraryState::Uninitialized,
+            // loaded when the vocabulary view is first opened
+            vocabulary_state: VocabularyState::Uninitialized,
             current_view: View::Info,
             db_connection,
             action_log: vec![],
@@ -38,6 +49,14 @@ impl TuiState {
     pub fn currently_input(&self) -> bool {
         match self.current_view {
             View::Books => matches!(self.books_state, BooksState::EnterToImport(..)),
+            View::Analysis => match &self.analysis_state {
+                AnalysisState::Extracted(extracted_state) => extracted_state.filter_input.is_some(),
+                AnalysisState::Blank => false,
+            },
+            View::Vocabulary => match &self.vocabulary_state {
+                VocabularyState::Display(display_state) => display_state.input.is_some(),
+                VocabularyState::Uninitialized => false,
+            },
             _ => false,
         }
     }
@@ -48,5 +67,7 @@ pub enum View {
     Books,
     Analysis,
     WordLists,
+    Library,
+    Vocabulary,
     Exit,
 }
@@ -1,8 +1,33 @@
 use crate::{
-    analysis::{get_analysis_info, AnalysisInfo, AnalysisQuery},
-    extraction::ExtractionResult,
+    analysis::{
+        get_analysis_info, get_chapter_stats, get_coverage_plan, get_filtered_extraction_items,
+        get_unknown_chars, get_word_coverage, sort_by_local_frequency, AnalysisInfo, AnalysisQuery,
+        ChapterStats, CoverageStep, UnknownCharInfo,
+    },
+    components::{
+        get_bundled_decompositions, get_component_analysis, get_decompositions, ComponentAnalysis,
+    },
+    db::{
+        cache::{db_cache_insert, db_cache_select},
+        vocab::VocabStatus,
+    },
+    extraction::{ExtractionItem, ExtractionResult},
+    grammar::{analyze_grammar, get_grammar_patterns, GrammarOccurrences},
+    lexical::{get_extraction_lexical_stats, LexicalStats},
+    vocabulary::{get_known_chars, VocabularyContext},
 };
-use std::collections::{HashMap, HashSet};
+use rusqlite::Connection;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+/// Amount of chapters after the reading position that count as upcoming
+pub const UPCOMING_CHAPTERS: usize = 3;
+
+/// Initially targeted word coverage in percent
+pub const DEFAULT_COVERAGE_TARGET: u64 = 95;
 
 #[derive(Default)]
 pub enum AnalysisState {
@@ -11,11 +36,112 @@ pub enum AnalysisState {
     Extracted(Box<ExtractedState>),
 }
 
+/// Detail section shown below the occurrence info
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisSection {
+    Grammar,
+    Words,
+    Characters,
+    Components,
+    Coverage,
+    Chapters,
+}
+
+impl AnalysisSection {
+    pub fn next(self) -> Self {
+        match self {
+            AnalysisSection::Grammar => AnalysisSection::Words,
+            AnalysisSection::Words => AnalysisSection::Characters,
+            AnalysisSection::Characters => AnalysisSection::Components,
+            AnalysisSection::Components => AnalysisSection::Coverage,
+            AnalysisSection::Coverage => AnalysisSection::Chapters,
+            AnalysisSection::Chapters => AnalysisSection::Grammar,
+        }
+    }
+}
+
+/// Next choice of Anki levels counting as known, raising the lowest known level
+/// until only mature words are known, then starting over from new words
+pub fn next_known_levels(known_levels: &[VocabStatus]) -> Vec<VocabStatus> {
+    let lowest = match known_levels.iter().min() {
+        Some(VocabStatus::New) => VocabStatus::Learning,
+        Some(VocabStatus::Learning) => VocabStatus::Young,
+        Some(VocabStatus::Young) => VocabStatus::Mature,
+        _ => VocabStatus::New,
+    };
+    VocabStatus::ANKI_LEVELS
+        .into_iter()
+        .filter(|level| *level >= lowest)
+        .collect()
+}
+
+/// Persistent cache of a book's query results at the vocabulary version of its known words
+pub struct QueryCache {
+    pub db_connection: Arc<Mutex<Connection>>,
+    pub book_hash: String,
+    pub vocab_version: i64,
+}
+
+impl QueryCache {
+    // cache failures are not fatal, the info is computed instead
+    fn get_or_compute(
+        &self,
+        query: &AnalysisQuery,
+        known_levels: &[VocabStatus],
+        exclude_ignored: bool,
+        compute: impl FnOnce() -> AnalysisInfo,
+    ) -> AnalysisInfo {
+        let key = query.cache_key(known_levels, exclude_ignored);
+        let cached = db_cache_select(
+            &self.db_connection.lock().unwrap(),
+            &self.book_hash,
+            &key,
+            self.vocab_version,
+        );
+        if let Ok(Some(info)) = cached {
+            return info;
+        }
+        let info = compute();
+        let _ = db_cache_insert(
+            &self.db_connection.lock().unwrap(),
+            &self.book_hash,
+            &key,
+            self.vocab_version,
+            &info,
+        );
+        info
+    }
+}
+
 pub struct ExtractedState {
     pub extraction_result: ExtractionResult,
     pub analysis_query: AnalysisQuery,
     pub analysis_infos: HashMap<AnalysisQuery, AnalysisInfo>,
-    pub known_words_and_chars: HashSet<String>,
+    pub query_cache: QueryCache,
+    // Anki levels the known words were selected by
+    pub known_levels: Vec<VocabStatus>,
+    pub vocabulary: VocabularyContext,
+    pub grammar_occurrences: Vec<GrammarOccurrences>,
+    // index of the grammar pattern whose chapters are shown
+    pub selected_grammar: usize,
+    pub section: AnalysisSection,
+    // index of the chapter the reader is currently at
+    pub reading_position: usize,
+    // unknown words of current query, ranked by frequency in upcoming chapters
+    pub unknown_words: Vec<ExtractionItem>,
+    pub unknown_chars: Vec<UnknownCharInfo>,
+    // components of the unknown characters shared with known ones
+    pub component_analysis: ComponentAnalysis,
+    // error reading the configured IDS file, the bundled data is used instead
+    pub ids_error: Option<String>,
+    pub word_coverage: f64,
+    // targeted word coverage in percent and the words to learn to reach it
+    pub coverage_target: u64,
+    pub coverage_plan: Vec<CoverageStep>,
+    pub chapter_stats: Vec<ChapterStats>,
+    pub lexical_stats: LexicalStats,
+    // partial filter expression while it is being entered
+    pub filter_input: Option<String>,
     pub book_title: String,
     pub book_author: String,
 }
@@ -23,72 +149,230 @@ pub struct ExtractedState {
 impl ExtractedState {
     pub fn new(
         extraction_result: ExtractionResult,
-        known_words_and_chars: HashSet<String>,
+        known_levels: Vec<VocabStatus>,
+        vocabulary: VocabularyContext,
         book_title: &str,
         book_author: &str,
+        query_cache: QueryCache,
     ) -> Self {
-        let query_all = AnalysisQuery {
-            min_occurrence_words: 1,
-            min_occurrence_unknown_chars: None,
-        };
-        let query_min3 = AnalysisQuery {
-            min_occurrence_words: 3,
-            min_occurrence_unknown_chars: None,
-        };
+        let exclude_ignored = vocabulary.exclude_ignored;
+        let ignored_for_counts = vocabulary.ignored_for_counts();
+        let query_all = AnalysisQuery::new(1);
+        let query_min3 = AnalysisQuery::new(3);
 
+        // filters are hashed by their source only, their compiled regexes do not affect keys
+        #[allow(clippy::mutable_key_type)]
         let mut analysis_infos = HashMap::new();
-        let info_all = get_analysis_info(
-            &extraction_result,
-            query_all.min_occurrence_words,
-            &known_words_and_chars,
-            query_all.min_occurrence_unknown_chars,
-        );
-        let info_min3 = get_analysis_info(
+        let info_all =
+            query_cache.get_or_compute(&query_all, &known_levels, exclude_ignored, || {
+                get_analysis_info(
+                    &extraction_result,
+                    &query_all,
+                    &vocabulary.known_words_and_chars,
+                    ignored_for_counts,
+                    &vocabulary.char_overrides,
+                )
+            });
+        let info_min3 =
+            query_cache.get_or_compute(&query_min3, &known_levels, exclude_ignored, || {
+                get_analysis_info(
+                    &extraction_result,
+                    &query_min3,
+                    &vocabulary.known_words_and_chars,
+                    ignored_for_counts,
+                    &vocabulary.char_overrides,
+                )
+            });
+        analysis_infos.insert(query_all, info_all);
+        analysis_infos.insert(query_min3.clone(), info_min3);
+        let grammar_occurrences =
+            analyze_grammar(&extraction_result.segmented_book, &get_grammar_patterns());
+        let known_words_and_chars = &vocabulary.known_words_and_chars;
+        let word_coverage = get_word_coverage(&extraction_result, known_words_and_chars);
+        let unknown_chars = get_unknown_chars(
             &extraction_result,
-            query_min3.min_occurrence_words,
-            &known_words_and_chars,
-            query_min3.min_occurrence_unknown_chars,
+            known_words_and_chars,
+            &vocabulary.char_overrides,
         );
-        analysis_infos.insert(query_all, info_all);
-        analysis_infos.insert(query_min3, info_min3);
+        let chapter_stats = get_chapter_stats(&extraction_result, known_words_and_chars);
+        let lexical_stats = get_extraction_lexical_stats(&extraction_result);
 
-        ExtractedState {
+        let mut state = ExtractedState {
             extraction_result,
             analysis_query: query_min3,
             analysis_infos,
-            known_words_and_chars,
+            query_cache,
+            known_levels,
+            vocabulary,
+            grammar_occurrences,
+            selected_grammar: 0,
+            section: AnalysisSection::Grammar,
+            reading_position: 0,
+            unknown_words: vec![],
+            unknown_chars,
+            component_analysis: ComponentAnalysis::default(),
+            ids_error: None,
+            word_coverage,
+            coverage_target: DEFAULT_COVERAGE_TARGET,
+            coverage_plan: vec![],
+            chapter_stats,
+            lexical_stats,
+            filter_input: None,
             book_title: book_title.to_string(),
             book_author: book_author.to_string(),
-        }
+        };
+        state.update_unknown_words();
+        state.update_coverage_plan();
+        state.update_component_analysis();
+        state
     }
 
     pub fn query_update(&mut self, query: AnalysisQuery) -> AnalysisInfo {
-        let info = self.query(query);
-        if self.analysis_infos.get(&query).is_none() {
-            self.analysis_infos.insert(query, info);
+        let info = self.query(&query);
+        self.analysis_infos.entry(query.clone()).or_insert(info);
+        if query != self.analysis_query {
+            self.analysis_query = query;
+            self.update_unknown_words();
         }
-        self.analysis_query = query;
         info
     }
 
-    pub fn query(&self, query: AnalysisQuery) -> AnalysisInfo {
-        if let Some(info) = self.analysis_infos.get(&query) {
+    /// Change which words count as known, recomputing everything depending on them
+    pub fn set_known(
+        &mut self,
+        known_levels: Vec<VocabStatus>,
+        known_words_and_chars: HashSet<String>,
+    ) {
+        self.known_levels = known_levels;
+        self.vocabulary.known_words_and_chars = known_words_and_chars;
+        self.word_coverage = get_word_coverage(
+            &self.extraction_result,
+            &self.vocabulary.known_words_and_chars,
+        );
+        self.unknown_chars = get_unknown_chars(
+            &self.extraction_result,
+            &self.vocabulary.known_words_and_chars,
+            &self.vocabulary.char_overrides,
+        );
+        self.chapter_stats = get_chapter_stats(
+            &self.extraction_result,
+            &self.vocabulary.known_words_and_chars,
+        );
+        self.analysis_infos.clear();
+        // the queries drawn every frame need to stay memoized
+        for query in [
+            AnalysisQuery::new(1),
+            AnalysisQuery::new(3),
+            self.analysis_query.clone(),
+        ] {
+            let info = self.query(&query);
+            self.analysis_infos.insert(query, info);
+        }
+        self.update_unknown_words();
+        self.update_coverage_plan();
+        self.update_component_analysis();
+    }
+
+    pub fn chapter_amount(&self) -> usize {
+        self.extraction_result.segmented_book.chapter_cuts.len()
+    }
+
+    pub fn upcoming_chapters(&self) -> Range<usize> {
+        let end = (self.reading_position + UPCOMING_CHAPTERS).min(self.chapter_amount());
+        self.reading_position..end
+    }
+
+    pub fn set_reading_position(&mut self, position: usize) {
+        let position = position.min(self.chapter_amount().saturating_sub(1));
+        if position != self.reading_position {
+            self.reading_position = position;
+            self.update_unknown_words();
+        }
+    }
+
+    pub fn chapter_titles(&self) -> Vec<String> {
+        self.extraction_result
+            .segmented_book
+            .chapter_cuts
+            .iter()
+            .map(|chapter| chapter.title.clone())
+            .collect()
+    }
+
+    pub fn set_selected_grammar(&mut self, index: usize) {
+        self.selected_grammar = index.min(self.grammar_occurrences.len().saturating_sub(1));
+    }
+
+    pub fn set_coverage_target(&mut self, target: u64) {
+        let target = target.clamp(1, 100);
+        if target != self.coverage_target {
+            self.coverage_target = target;
+            self.update_coverage_plan();
+        }
+    }
+
+    fn update_coverage_plan(&mut self) {
+        self.coverage_plan = get_coverage_plan(
+            &self.extraction_result,
+            &self.vocabulary.known_words_and_chars,
+            self.coverage_target as f64 / 100.0,
+        );
+    }
+
+    fn update_component_analysis(&mut self) {
+        let known_chars = get_known_chars(
+            &self.vocabulary.known_words_and_chars,
+            &self.vocabulary.char_overrides,
+        );
+        let decompositions = match get_decompositions() {
+            Ok(decompositions) => decompositions,
+            Err(e) => {
+                self.ids_error = Some(format!("{:#}", e));
+                get_bundled_decompositions()
+            }
+        };
+        self.component_analysis =
+            get_component_analysis(&self.unknown_chars, &known_chars, decompositions);
+    }
+
+    fn update_unknown_words(&mut self) {
+        let mut items: Vec<&ExtractionItem> = get_filtered_extraction_items(
+            &self.extraction_result,
+            &self.analysis_query,
+            &self.vocabulary.known_words_and_chars,
+            &self.vocabulary.char_overrides,
+        )
+        .into_iter()
+        .filter(|item| !self.vocabulary.known_words_and_chars.contains(&item.word))
+        .filter(|item| !self.vocabulary.ignored_words.contains(&item.word))
+        .collect();
+        sort_by_local_frequency(&mut items, self.upcoming_chapters());
+        self.unknown_words = items.into_iter().cloned().collect();
+    }
+
+    pub fn query(&self, query: &AnalysisQuery) -> AnalysisInfo {
+        if let Some(info) = self.analysis_infos.get(query) {
             *info
         } else {
-            get_analysis_info(
-                &self.extraction_result,
-                query.min_occurrence_words,
-                &self.known_words_and_chars,
-                query.min_occurrence_unknown_chars,
+            self.query_cache.get_or_compute(
+                query,
+                &self.known_levels,
+                self.vocabulary.exclude_ignored,
+                || {
+                    get_analysis_info(
+                        &self.extraction_result,
+                        query,
+                        &self.vocabulary.known_words_and_chars,
+                        self.vocabulary.ignored_for_counts(),
+                        &self.vocabulary.char_overrides,
+                    )
+                },
             )
         }
     }
 
     pub fn query_all(&self) -> AnalysisInfo {
-        self.query(AnalysisQuery {
-            min_occurrence_words: 1,
-            min_occurrence_unknown_chars: None,
-        })
+        self.query(&AnalysisQuery::new(1))
     }
 
     pub fn query_current(&self) -> AnalysisInfo {
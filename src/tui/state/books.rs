@@ -1,7 +1,7 @@
 use anyhow::Result;
 use std::{
     cell::RefCell,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{
         mpsc::{self, Receiver},
         Arc, Mutex,
@@ -12,18 +12,29 @@ use std::{
 use tui::widgets::TableState;
 
 use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    analysis::{compare_books, BookComparison},
+    config::get_config,
+    db::chars::db_char_overrides_select,
+    db::levels::db_levels_select_all,
     db::{
         books::{db_books_insert, db_books_select_all},
-        vocab::db_words_select_known,
+        cache::{db_cache_insert, db_cache_select, db_vocab_version},
+        vocab::{db_words_select_known, describe_known_levels},
     },
     ebook::FlatBook,
-    extraction::word_to_hanzi,
+    extraction::{extract_vocab_from_segmented, word_to_hanzi},
+    levels::WordLevel,
+    lexical::{get_lexical_stats, LexicalStats},
     segmentation::{segment_book, BookSegmentation},
     vocabulary::get_known_words_and_chars,
 };
 
+/// Key of a book's stats in the analysis cache
+const BOOK_STATS_CACHE_KEY: &str = "book-stats";
+
 pub enum BooksState {
     Uninitialized,
     Calculating(CalculatingState),
@@ -31,52 +42,88 @@ pub enum BooksState {
     // String arg: partial file path
     EnterToImport(String),
     Importing(ImportingState),
+    Comparing(Box<ComparingState>),
 }
 
 impl BooksState {
     pub fn init(db_connection: Arc<Mutex<Connection>>) -> Result<Self> {
         let books = db_books_select_all(&db_connection.lock().unwrap())?;
-        let known_words = db_words_select_known(&db_connection.lock().unwrap())?;
-        let known_words_and_chars = get_known_words_and_chars(known_words);
+        let known_words = db_words_select_known(
+            &db_connection.lock().unwrap(),
+            &get_config().anki_levels.known,
+        )?;
+        let char_overrides = db_char_overrides_select(&db_connection.lock().unwrap())?;
+        let known_words_and_chars = get_known_words_and_chars(known_words, &char_overrides);
+        let levels = db_levels_select_all(&db_connection.lock().unwrap())?;
+        let vocab_version = db_vocab_version(&db_connection.lock().unwrap())?;
         Ok(Self::Calculating(CalculatingState::new(
             books,
             known_words_and_chars,
+            levels,
+            vocab_version,
+            db_connection,
         )))
     }
 }
 
 pub struct CalculatingState {
-    // (title, author, book)
-    pub books: Vec<(String, String, BookSegmentation)>,
+    // (title, author, book, content hash)
+    pub books: Vec<(String, String, BookSegmentation, String)>,
     pub known_words_and_chars: HashSet<String>,
+    pub levels: HashMap<String, WordLevel>,
+    pub vocab_version: i64,
+    pub db_connection: Arc<Mutex<Connection>>,
     pub start: Instant,
 }
 
 impl CalculatingState {
     pub fn new(
-        books: Vec<(String, String, BookSegmentation)>,
+        books: Vec<(String, String, BookSegmentation, String)>,
         known_words_and_chars: HashSet<String>,
+        levels: HashMap<String, WordLevel>,
+        vocab_version: i64,
+        db_connection: Arc<Mutex<Connection>>,
     ) -> Self {
         Self {
             books,
             known_words_and_chars,
+            levels,
+            vocab_version,
+            db_connection,
             start: Instant::now(),
         }
     }
 
+    // stats are only computed for books without up to date cache entry
     pub fn update(&self) -> BooksState {
+        let conn = self.db_connection.lock().unwrap();
+        let cache_key = format!(
+            "{} {}",
+            BOOK_STATS_CACHE_KEY,
+            describe_known_levels(&get_config().anki_levels.known)
+        );
         let mut books_with_stats = Vec::with_capacity(self.books.len());
-        for (title, author, book) in &self.books {
-            books_with_stats.push(get_enrich_book_with_stats(
-                title.clone(),
-                author.clone(),
-                book.clone(),
-                &self.known_words_and_chars,
-            ))
+        for (title, author, book, book_hash) in &self.books {
+            let cached = db_cache_select(&conn, book_hash, &cache_key, self.vocab_version)
+                .ok()
+                .flatten();
+            let stats = cached.unwrap_or_else(|| {
+                let stats = get_book_stats(book, &self.known_words_and_chars, &self.levels);
+                let _ = db_cache_insert(&conn, book_hash, &cache_key, self.vocab_version, &stats);
+                stats
+            });
+            books_with_stats.push(BookWithStats {
+                title: title.clone(),
+                author: author.clone(),
+                book: book.clone(),
+                book_hash: book_hash.clone(),
+                stats,
+            })
         }
         BooksState::Display(DisplayState::new(
             books_with_stats,
             self.known_words_and_chars.clone(),
+            self.vocab_version,
         ))
     }
 
@@ -85,12 +132,12 @@ impl CalculatingState {
     }
 }
 
-pub fn get_enrich_book_with_stats(
-    title: String,
-    author: String,
-    book: BookSegmentation,
+pub fn get_book_stats(
+    book: &BookSegmentation,
     known_words: &HashSet<String>,
-) -> BookWithStats {
+    levels: &HashMap<String, WordLevel>,
+) -> BookStats {
+    let lexical = get_lexical_stats(book, levels);
     let mut word_sequence = Vec::new();
     for chapter in &book.chapter_cuts {
         word_sequence.extend(&chapter.cut);
@@ -107,12 +154,10 @@ pub fn get_enrich_book_with_stats(
         let chars = word_to_hanzi(word);
         total_chars += chars.len();
     }
-    BookWithStats {
-        book,
+    BookStats {
         word_comprehension: total_words_known as f64 / total_words as f64,
         total_chars,
-        title,
-        author,
+        lexical,
     }
 }
 
@@ -122,12 +167,17 @@ pub struct DisplayState {
     pub sort_by: SortType,
     pub table_state: RefCell<TableState>,
     pub known_words_and_chars: HashSet<String>,
+    // vocabulary version the known words were selected at
+    pub vocab_version: i64,
+    // index of book marked for comparison
+    pub marked: Option<usize>,
 }
 
 impl DisplayState {
     pub fn new(
         books_with_stats: Vec<BookWithStats>,
         known_words_and_chars: HashSet<String>,
+        vocab_version: i64,
     ) -> Self {
         Self {
             books_with_stats,
@@ -135,6 +185,26 @@ impl DisplayState {
             sort_by: SortType::Comprehension,
             table_state: RefCell::new(TableState::default()),
             known_words_and_chars,
+            vocab_version,
+            marked: None,
+        }
+    }
+
+    /// Mark current book for comparison, or unmark it if it already is
+    pub fn toggle_mark(&mut self) {
+        let selected = self.table_state.borrow().selected();
+        self.marked = if self.marked == selected {
+            None
+        } else {
+            selected
+        };
+    }
+
+    /// Indices of marked book and current book, if they are different
+    pub fn marked_and_current(&self) -> Option<(usize, usize)> {
+        match (self.marked, self.table_state.borrow().selected()) {
+            (Some(a), Some(b)) if a != b => Some((a, b)),
+            _ => None,
         }
     }
 
@@ -198,10 +268,64 @@ impl DisplayState {
             None => (None, None),
         };
         self.table_state.borrow_mut().select(to_select);
+        self.marked = None;
         book
     }
 }
 
+pub struct ComparingState {
+    pub title_a: String,
+    pub title_b: String,
+    pub comparison: BookComparison,
+    pub table_state: RefCell<TableState>,
+    // books view to return to
+    pub display_state: DisplayState,
+}
+
+impl ComparingState {
+    /// Compare book a with book b of the books view
+    pub fn new(display_state: DisplayState, a: usize, b: usize) -> Self {
+        let book_a = &display_state.books_with_stats[a];
+        let book_b = &display_state.books_with_stats[b];
+        let comparison = compare_books(
+            &extract_vocab_from_segmented(book_a.book.clone()),
+            &extract_vocab_from_segmented(book_b.book.clone()),
+            &display_state.known_words_and_chars,
+        );
+        Self {
+            title_a: book_a.title.clone(),
+            title_b: book_b.title.clone(),
+            comparison,
+            table_state: RefCell::new(TableState::default()),
+            display_state,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        let len = self.comparison.shared_unknown.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.borrow().selected() {
+            Some(i) if i < len - 1 => i + 1,
+            _ => 0,
+        };
+        self.table_state.borrow_mut().select(Some(i));
+    }
+
+    pub fn select_previous(&mut self) {
+        let len = self.comparison.shared_unknown.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.table_state.borrow().selected() {
+            Some(0) | None => len - 1,
+            Some(i) => i - 1,
+        };
+        self.table_state.borrow_mut().select(Some(i));
+    }
+}
+
 pub struct ImportingState {
     pub book_title: String,
     pub book_author: String,
@@ -266,8 +390,16 @@ pub struct BookWithStats {
     pub title: String,
     pub author: String,
     pub book: BookSegmentation,
+    pub book_hash: String,
+    pub stats: BookStats,
+}
+
+/// Stats of a book that only change with the known vocabulary (or levels), cached per book
+#[derive(Serialize, Deserialize)]
+pub struct BookStats {
     pub word_comprehension: f64,
     pub total_chars: usize,
+    pub lexical: LexicalStats,
 }
 
 pub enum SortType {
@@ -11,7 +11,8 @@ use std::{
 use rusqlite::Connection;
 
 use crate::{
-    db::anki::db_sync_anki_data,
+    db::{anki::db_sync_anki_data, history::db_history_select_all},
+    history::{record_vocab_snapshot, VocabSnapshot},
     vocabulary::{get_vocab_stats, VocabularyInfo},
 };
 
@@ -25,18 +26,26 @@ pub enum InfoState {
 impl InfoState {
     // getting vocab info is very fast, ok to block main thread
     pub fn init(db_connection: Arc<Mutex<Connection>>) -> Result<Self> {
-        get_vocab_stats(&db_connection.lock().unwrap()).map(|vocab_info| {
-            InfoState::Display(DisplayState {
-                previous_vocab_info: None,
-                vocab_info,
-            })
-        })
+        let conn = db_connection.lock().unwrap();
+        let mut history = db_history_select_all(&conn)?;
+        // start history with the current vocabulary
+        if history.is_empty() {
+            record_vocab_snapshot(&conn)?;
+            history = db_history_select_all(&conn)?;
+        }
+        let vocab_info = get_vocab_stats(&conn)?;
+        Ok(InfoState::Display(DisplayState {
+            previous_vocab_info: None,
+            vocab_info,
+            history,
+        }))
     }
 }
 
 pub struct DisplayState {
     pub previous_vocab_info: Option<VocabularyInfo>,
     pub vocab_info: VocabularyInfo,
+    pub history: Vec<VocabSnapshot>,
 }
 
 impl DisplayState {
@@ -53,26 +62,36 @@ impl DisplayState {
 
 pub struct SyncingState {
     pub previous_vocab_info: VocabularyInfo,
-    pub receiver: Receiver<Result<VocabularyInfo>>,
+    pub previous_history: Vec<VocabSnapshot>,
+    // vocab info and history after the sync
+    pub receiver: Receiver<Result<(VocabularyInfo, Vec<VocabSnapshot>)>>,
     pub syncing_thread: JoinHandle<()>,
     pub start: Instant,
 }
 
 pub struct SyncErrorState {
     pub previous_vocab_info: VocabularyInfo,
+    pub previous_history: Vec<VocabSnapshot>,
     pub error_msg: String,
 }
 
 impl SyncingState {
-    pub fn new(previous_vocab_info: VocabularyInfo, db_connection: Arc<Mutex<Connection>>) -> Self {
+    pub fn new(
+        previous_vocab_info: VocabularyInfo,
+        previous_history: Vec<VocabSnapshot>,
+        db_connection: Arc<Mutex<Connection>>,
+    ) -> Self {
         let (tx, rx) = mpsc::channel();
         let syncing_thread = thread::spawn(move || {
             let mut db_conn = db_connection.lock().unwrap();
-            let res = db_sync_anki_data(&mut db_conn).and_then(|()| get_vocab_stats(&db_conn));
+            let res = db_sync_anki_data(&mut db_conn)
+                .and_then(|()| record_vocab_snapshot(&db_conn))
+                .and_then(|info| Ok((info, db_history_select_all(&db_conn)?)));
             tx.send(res).expect("could not send event");
         });
         Self {
             previous_vocab_info,
+            previous_history,
             receiver: rx,
             syncing_thread,
             start: Instant::now(),
@@ -84,12 +103,14 @@ impl SyncingState {
     pub fn update(&mut self) -> Option<InfoState> {
         match self.receiver.try_recv() {
             Ok(res) => match res {
-                Ok(new_vocab_info) => Some(InfoState::Display(DisplayState {
+                Ok((new_vocab_info, history)) => Some(InfoState::Display(DisplayState {
                     previous_vocab_info: Some(self.previous_vocab_info),
                     vocab_info: new_vocab_info,
+                    history,
                 })),
                 Err(e) => Some(InfoState::SyncError(SyncErrorState {
                     previous_vocab_info: self.previous_vocab_info,
+                    previous_history: std::mem::take(&mut self.previous_history),
                     error_msg: format!("Anki sync error: {e}"),
                 })),
             },
@@ -97,6 +118,7 @@ impl SyncingState {
                 mpsc::TryRecvError::Empty => None,
                 mpsc::TryRecvError::Disconnected => Some(InfoState::SyncError(SyncErrorState {
                     previous_vocab_info: self.previous_vocab_info,
+                    previous_history: std::mem::take(&mut self.previous_history),
                     error_msg: e.to_string(),
                 })),
             },
@@ -0,0 +1,269 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tui::widgets::TableState;
+
+use rusqlite::Connection;
+
+use crate::{
+    config::get_config,
+    corpus::{plan_reading_order, BookVocabulary, CorpusIndex, CorpusWord, ReadingStep},
+    db::{
+        books::db_books_select_all, chars::db_char_overrides_select, vocab::db_words_select_known,
+    },
+    segmentation::BookSegmentation,
+    vocabulary::get_known_words_and_chars,
+};
+
+/// Initially targeted share of known words (in percent) when starting a book
+pub const DEFAULT_READING_TARGET: u64 = 90;
+
+/// Words occurring at least this often in a book are assumed to be learned by reading it
+pub const PLANNER_MIN_FREQUENCY: u64 = 3;
+
+pub enum LibraryState {
+    Uninitialized,
+    Calculating(CalculatingState),
+    Display(Box<DisplayState>),
+}
+
+impl LibraryState {
+    pub fn init(db_connection: Arc<Mutex<Connection>>) -> Result<Self> {
+        let books = db_books_select_all(&db_connection.lock().unwrap())?;
+        let known_words = db_words_select_known(
+            &db_connection.lock().unwrap(),
+            &get_config().anki_levels.known,
+        )?;
+        let char_overrides = db_char_overrides_select(&db_connection.lock().unwrap())?;
+        let known_words_and_chars = get_known_words_and_chars(known_words, &char_overrides);
+        Ok(Self::Calculating(CalculatingState {
+            books: books
+                .into_iter()
+                .map(|(title, author, book, _)| (title, author, book))
+                .collect(),
+            known_words_and_chars,
+            start: Instant::now(),
+        }))
+    }
+}
+
+pub struct CalculatingState {
+    // (title, author, book)
+    pub books: Vec<(String, String, BookSegmentation)>,
+    pub known_words_and_chars: HashSet<String>,
+    pub start: Instant,
+}
+
+impl CalculatingState {
+    pub fn update(&self) -> LibraryState {
+        let index = CorpusIndex::build(
+            &self
+                .books
+                .iter()
+                .map(|(_, _, book)| book)
+                .collect::<Vec<_>>(),
+        );
+        // with several books, only words shared between books are of interest
+        let min_books = if index.book_amount > 1 { 2 } else { 1 };
+        let recommendations = index.recommendations(&self.known_words_and_chars, min_books);
+        let book_vocabularies = self
+            .books
+            .iter()
+            .map(|(title, author, book)| BookVocabulary::new(title, author, book))
+            .collect();
+        let mut state = DisplayState {
+            book_amount: index.book_amount,
+            total_words: index.total_words,
+            min_books,
+            recommendations,
+            table_state: RefCell::new(TableState::default()),
+            section: LibrarySection::Words,
+            known_words_and_chars: self.known_words_and_chars.clone(),
+            book_vocabularies,
+            excluded_books: HashSet::new(),
+            reading_target: DEFAULT_READING_TARGET,
+            reading_order: vec![],
+            order_table_state: RefCell::new(TableState::default()),
+        };
+        state.update_reading_order();
+        LibraryState::Display(Box::new(state))
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LibrarySection {
+    Words,
+    ReadingOrder,
+}
+
+pub struct DisplayState {
+    pub book_amount: usize,
+    pub total_words: u64,
+    pub min_books: usize,
+    pub recommendations: Vec<CorpusWord>,
+    pub table_state: RefCell<TableState>,
+    pub section: LibrarySection,
+    pub known_words_and_chars: HashSet<String>,
+    pub book_vocabularies: Vec<BookVocabulary>,
+    // (title, author) of books left out of the reading order
+    pub excluded_books: HashSet<(String, String)>,
+    // targeted share of known words in percent
+    pub reading_target: u64,
+    pub reading_order: Vec<ReadingStep>,
+    pub order_table_state: RefCell<TableState>,
+}
+
+impl DisplayState {
+    pub fn toggle_section(&mut self) {
+        self.section = match self.section {
+            LibrarySection::Words => LibrarySection::ReadingOrder,
+            LibrarySection::ReadingOrder => LibrarySection::Words,
+        };
+    }
+
+    /// (title, author) in display order of the reading order section: planned books, then excluded ones
+    pub fn reading_order_books(&self) -> Vec<(&str, &str)> {
+        let mut excluded: Vec<(&str, &str)> = self
+            .excluded_books
+            .iter()
+            .map(|(title, author)| (title.as_str(), author.as_str()))
+            .collect();
+        excluded.sort();
+        self.reading_order
+            .iter()
+            .map(|step| (step.title.as_str(), step.author.as_str()))
+            .chain(excluded)
+            .collect()
+    }
+
+    /// Include or exclude the selected book of the reading order section
+    pub fn toggle_selected_book(&mut self) {
+        let selected = self.order_table_state.borrow().selected();
+        let book = match selected.and_then(|i| self.reading_order_books().get(i).copied()) {
+            Some((title, author)) => (title.to_string(), author.to_string()),
+            None => return,
+        };
+        if !self.excluded_books.remove(&book) {
+            self.excluded_books.insert(book);
+        }
+        self.update_reading_order();
+    }
+
+    pub fn set_reading_target(&mut self, target: u64) {
+        self.reading_target = target.clamp(1, 100);
+        self.update_reading_order();
+    }
+
+    /// Planned step of the book selected in the reading order section
+    pub fn selected_step(&self) -> Option<&ReadingStep> {
+        let selected = self.order_table_state.borrow().selected()?;
+        self.reading_order.get(selected)
+    }
+
+    fn update_reading_order(&mut self) {
+        let books: Vec<&BookVocabulary> = self
+            .book_vocabularies
+            .iter()
+            .filter(|book| {
+                !self
+                    .excluded_books
+                    .contains(&(book.title.clone(), book.author.clone()))
+            })
+            .collect();
+        self.reading_order = plan_reading_order(
+            &books,
+            &self.known_words_and_chars,
+            PLANNER_MIN_FREQUENCY,
+            self.reading_target as f64 / 100.0,
+        );
+    }
+
+    fn current_len_and_table_state(&self) -> (usize, &RefCell<TableState>) {
+        match self.section {
+            LibrarySection::Words => (self.recommendations.len(), &self.table_state),
+            LibrarySection::ReadingOrder => (self.book_vocabularies.len(), &self.order_table_state),
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        let (len, table_state) = self.current_len_and_table_state();
+        if len == 0 {
+            return;
+        }
+        let i = match table_state.borrow().selected() {
+            Some(i) if i < len - 1 => i + 1,
+            _ => 0,
+        };
+        table_state.borrow_mut().select(Some(i));
+    }
+
+    pub fn select_previous(&mut self) {
+        let (len, table_state) = self.current_len_and_table_state();
+        if len == 0 {
+            return;
+        }
+        let i = match table_state.borrow().selected() {
+            Some(0) | None => len - 1,
+            Some(i) => i - 1,
+        };
+        table_state.borrow_mut().select(Some(i));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Instant};
+
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+    use crate::tui::state::library::{CalculatingState, LibraryState};
+
+    #[test]
+    fn exclude_one_of_two_books_with_same_title() {
+        let book = |words: &[&str]| BookSegmentation {
+            title: "红楼梦".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: words.iter().map(|w| w.to_string()).collect(),
+                sentences: vec![],
+            }],
+        };
+        let calculating = CalculatingState {
+            books: vec![
+                (
+                    "红楼梦".to_string(),
+                    "曹雪芹".to_string(),
+                    book(&["我", "猫"]),
+                ),
+                (
+                    "红楼梦".to_string(),
+                    "高鹗".to_string(),
+                    book(&["我", "狗"]),
+                ),
+            ],
+            known_words_and_chars: HashSet::from(["我".to_string()]),
+            start: Instant::now(),
+        };
+        let mut state = match calculating.update() {
+            LibraryState::Display(state) => state,
+            _ => panic!("library should be displayed"),
+        };
+        assert_eq!(state.reading_order.len(), 2);
+        state.order_table_state.borrow_mut().select(Some(0));
+        let excluded_author = state.reading_order[0].author.clone();
+        state.toggle_selected_book();
+        assert_eq!(state.reading_order.len(), 1);
+        assert_ne!(state.reading_order[0].author, excluded_author);
+        assert_eq!(
+            state.reading_order_books()[1],
+            ("红楼梦", excluded_author.as_str())
+        );
+    }
+}
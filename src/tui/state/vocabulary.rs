@@ -0,0 +1,413 @@
+use anyhow::Result;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use tui::widgets::TableState;
+
+use rusqlite::Connection;
+
+use crate::{
+    config::get_config,
+    db::{
+        books::db_books_select_all,
+        chars::{db_char_overrides_del, db_char_overrides_select, db_char_overrides_set},
+        ignore::{db_ignored_del, db_ignored_select_all, IgnoredWord},
+        vocab::{
+            db_words_external_add, db_words_external_del, db_words_select_detailed, ExternalSource,
+            VocabWord,
+        },
+        word_lists::{db_wlist_select_all_mdata, db_wlist_select_by_id},
+    },
+    history::record_vocab_snapshot,
+    vocabulary::{split_chars, CharOverrides},
+    word_lists::Category,
+};
+
+pub enum VocabularyState {
+    Uninitialized,
+    Display(Box<DisplayState>),
+}
+
+impl VocabularyState {
+    pub fn init(db_connection: Arc<Mutex<Connection>>) -> Result<Self> {
+        let mut state = DisplayState {
+            words: vec![],
+            ignored: vec![],
+            show_ignored: false,
+            visible: vec![],
+            search: String::new(),
+            input: None,
+            table_state: RefCell::new(TableState::default()),
+            char_overrides: CharOverrides::default(),
+            books: vec![],
+            word_lists: vec![],
+            db_connection,
+        };
+        state.reload()?;
+        Ok(Self::Display(Box::new(state)))
+    }
+}
+
+/// Text being entered in the vocabulary view
+pub enum VocabularyInput {
+    /// incremental search, applied while typing
+    Search,
+    /// word to add as external word
+    Add(String),
+    /// characters to mark as known (true) or unknown (false)
+    MarkChars(bool, String),
+    /// characters to remove the marks of
+    UnmarkChars(String),
+}
+
+impl VocabularyInput {
+    /// Entered text of the input, None for the search which is kept in the state
+    pub fn text_mut(&mut self) -> Option<&mut String> {
+        match self {
+            VocabularyInput::Search => None,
+            VocabularyInput::Add(text)
+            | VocabularyInput::MarkChars(_, text)
+            | VocabularyInput::UnmarkChars(text) => Some(text),
+        }
+    }
+}
+
+pub struct DisplayState {
+    // all words, sorted
+    pub words: Vec<VocabWord>,
+    // words of the global ignore list, oldest first
+    pub ignored: Vec<IgnoredWord>,
+    // whether the ignored words are listed instead of the vocabulary
+    pub show_ignored: bool,
+    // indices of the listed words matching the search
+    pub visible: Vec<usize>,
+    pub search: String,
+    pub input: Option<VocabularyInput>,
+    pub table_state: RefCell<TableState>,
+    pub char_overrides: CharOverrides,
+    // (title, word frequencies) of stored books
+    pub books: Vec<(String, HashMap<String, u64>)>,
+    // (name, tagged words) of word lists
+    pub word_lists: Vec<(String, HashMap<String, Category>)>,
+    pub db_connection: Arc<Mutex<Connection>>,
+}
+
+impl DisplayState {
+    /// Load words, stored books and word lists again, e.g after they changed in other tabs,
+    /// keeping search and selection
+    pub fn reload(&mut self) -> Result<()> {
+        let conn = self.db_connection.lock().unwrap();
+        self.words = db_words_select_detailed(&conn, &get_config().anki_levels.known)?;
+        self.char_overrides = db_char_overrides_select(&conn)?;
+        self.ignored = db_ignored_select_all(&conn)?;
+        self.books = db_books_select_all(&conn)?
+            .into_iter()
+            .map(|(title, _, book, _)| {
+                let mut frequencies: HashMap<String, u64> = HashMap::new();
+                for chapter in &book.chapter_cuts {
+                    for word in &chapter.cut {
+                        *frequencies.entry(word.clone()).or_default() += 1;
+                    }
+                }
+                (title, frequencies)
+            })
+            .collect();
+        self.word_lists = vec![];
+        for metadata in db_wlist_select_all_mdata(&conn)? {
+            let chapters = db_wlist_select_by_id(&conn, metadata.id)?.unwrap_or_default();
+            let tags: HashMap<String, Category> = chapters
+                .into_iter()
+                .flat_map(|chapter| chapter.tagged_words)
+                .filter_map(|tw| tw.category.map(|category| (tw.word, category)))
+                .collect();
+            self.word_lists.push((metadata.to_string(), tags));
+        }
+        drop(conn);
+        let selected = self.table_state.borrow().selected();
+        self.update_visible();
+        self.keep_selection(selected);
+        Ok(())
+    }
+
+    pub fn set_search(&mut self, search: String) {
+        self.search = search;
+        self.update_visible();
+    }
+
+    /// Switch between listing the vocabulary and the ignored words
+    pub fn toggle_ignored(&mut self) {
+        self.show_ignored = !self.show_ignored;
+        self.update_visible();
+    }
+
+    fn update_visible(&mut self) {
+        let search = self.search.trim();
+        let listed: Vec<&str> = if self.show_ignored {
+            self.ignored.iter().map(|w| w.word.as_str()).collect()
+        } else {
+            self.words.iter().map(|w| w.word.as_str()).collect()
+        };
+        self.visible = listed
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| word.contains(search))
+            .map(|(i, _)| i)
+            .collect();
+        let selected = if self.visible.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.table_state.borrow_mut().select(selected);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let i = match self.table_state.borrow().selected() {
+            Some(i) if i < self.visible.len() - 1 => i + 1,
+            _ => 0,
+        };
+        self.table_state.borrow_mut().select(Some(i));
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let i = match self.table_state.borrow().selected() {
+            Some(0) | None => self.visible.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.table_state.borrow_mut().select(Some(i));
+    }
+
+    /// Selected vocabulary word, None while listing ignored words
+    pub fn get_current(&self) -> Option<&VocabWord> {
+        if self.show_ignored {
+            return None;
+        }
+        let i = self.table_state.borrow().selected()?;
+        self.visible.get(i).map(|&index| &self.words[index])
+    }
+
+    /// Selected ignored word, None while listing the vocabulary
+    pub fn get_current_ignored(&self) -> Option<&IgnoredWord> {
+        if !self.show_ignored {
+            return None;
+        }
+        let i = self.table_state.borrow().selected()?;
+        self.visible.get(i).map(|&index| &self.ignored[index])
+    }
+
+    /// Selected word of either list
+    pub fn current_word(&self) -> Option<&str> {
+        self.get_current()
+            .map(|word| word.word.as_str())
+            .or_else(|| self.get_current_ignored().map(|word| word.word.as_str()))
+    }
+
+    /// Stored books containing the word with its frequency in each
+    pub fn books_containing(&self, word: &str) -> Vec<(&str, u64)> {
+        self.books
+            .iter()
+            .filter_map(|(title, frequencies)| {
+                frequencies
+                    .get(word)
+                    .map(|frequency| (title.as_str(), *frequency))
+            })
+            .collect()
+    }
+
+    /// Word lists in which the word was tagged, with its category
+    pub fn word_lists_tagging(&self, word: &str) -> Vec<(&str, Category)> {
+        self.word_lists
+            .iter()
+            .filter_map(|(name, tags)| tags.get(word).map(|category| (name.as_str(), *category)))
+            .collect()
+    }
+
+    /// Characters of the word that are marked, with whether they are marked as known
+    pub fn marked_chars<'a>(&self, word: &'a str) -> Vec<(&'a str, bool)> {
+        split_chars(word)
+            .into_iter()
+            .filter_map(|hanzi| {
+                if self.char_overrides.known.contains(hanzi) {
+                    Some((hanzi, true))
+                } else if self.char_overrides.unknown.contains(hanzi) {
+                    Some((hanzi, false))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Mark characters as known/unknown, or remove their marks if known is None,
+    /// returns the characters that changed
+    pub fn mark_chars(&mut self, characters: &str, known: Option<bool>) -> Result<Vec<String>> {
+        let chars = split_chars(characters);
+        let mut conn = self.db_connection.lock().unwrap();
+        let changed = match known {
+            Some(known) => db_char_overrides_set(&mut conn, &chars, known)?,
+            None => db_char_overrides_del(&mut conn, &chars)?,
+        };
+        if !changed.is_empty() {
+            record_vocab_snapshot(&conn)?;
+        }
+        self.char_overrides = db_char_overrides_select(&conn)?;
+        Ok(changed)
+    }
+
+    /// Add word as manually added external word, returns whether it was new
+    pub fn add_external(&mut self, word: &str) -> Result<bool> {
+        let mut conn = self.db_connection.lock().unwrap();
+        let added = db_words_external_add(
+            &mut conn,
+            &HashSet::from([word]),
+            &ExternalSource::Manual,
+            None,
+        )?;
+        if !added.is_empty() {
+            record_vocab_snapshot(&conn)?;
+        }
+        self.words = db_words_select_detailed(&conn, &get_config().anki_levels.known)?;
+        drop(conn);
+        self.update_visible();
+        // select the new word if it matches the search
+        if let Some(i) = self
+            .visible
+            .iter()
+            .position(|&i| self.words[i].word == word)
+        {
+            self.table_state.borrow_mut().select(Some(i));
+        }
+        Ok(!added.is_empty())
+    }
+
+    /// Remove current word from the ignore list, returns the removed word
+    pub fn remove_current_ignored(&mut self) -> Result<Option<String>> {
+        let selected = self.table_state.borrow().selected();
+        let word = match self.get_current_ignored() {
+            Some(word) => word.word.clone(),
+            None => return Ok(None),
+        };
+        let mut conn = self.db_connection.lock().unwrap();
+        db_ignored_del(
+            &mut conn,
+            &HashSet::from([word.clone()]),
+            get_config().ignore.exclude_from_counts,
+        )?;
+        self.ignored = db_ignored_select_all(&conn)?;
+        drop(conn);
+        self.update_visible();
+        self.keep_selection(selected);
+        Ok(Some(word))
+    }
+
+    // select the same position in the table again, if there still are rows
+    fn keep_selection(&mut self, selected: Option<usize>) {
+        if let Some(i) = selected {
+            if !self.visible.is_empty() {
+                let i = i.min(self.visible.len() - 1);
+                self.table_state.borrow_mut().select(Some(i));
+            }
+        }
+    }
+
+    /// Delete current word if it was added externally, returns the deleted word
+    pub fn delete_current_external(&mut self) -> Result<Option<String>> {
+        let selected = self.table_state.borrow().selected();
+        let word = match self.get_current() {
+            Some(word) if word.external.is_some() => word.word.clone(),
+            _ => return Ok(None),
+        };
+        let mut conn = self.db_connection.lock().unwrap();
+        db_words_external_del(&mut conn, &HashSet::from([word.clone()]))?;
+        record_vocab_snapshot(&conn)?;
+        self.words = db_words_select_detailed(&conn, &get_config().anki_levels.known)?;
+        drop(conn);
+        self.update_visible();
+        // keep position in the table, the word may remain as Anki word
+        self.keep_selection(selected);
+        Ok(Some(word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex},
+    };
+
+    use crate::config::init_test_config;
+    use crate::db::{
+        books::db_books_insert,
+        open_test_db,
+        vocab::{
+            db_words_anki_update, db_words_external_add, AnkiWord, ExternalSource, VocabStatus,
+        },
+    };
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+    use crate::tui::state::vocabulary::VocabularyState;
+
+    #[test]
+    fn search_and_reload_words() {
+        init_test_config();
+        let mut conn = open_test_db();
+        let anki_word = |status| AnkiWord {
+            status,
+            last_modified: 0,
+        };
+        let anki = HashMap::from([
+            ("猫".to_string(), anki_word(VocabStatus::Mature)),
+            ("猫头鹰".to_string(), anki_word(VocabStatus::Suspended)),
+        ]);
+        db_words_anki_update(&mut conn, &anki).unwrap();
+        let words = HashSet::from(["猫头鹰", "狗"]);
+        db_words_external_add(&mut conn, &words, &ExternalSource::Manual, None).unwrap();
+        let db_connection = Arc::new(Mutex::new(conn));
+        let mut state = match VocabularyState::init(db_connection.clone()).unwrap() {
+            VocabularyState::Display(state) => state,
+            VocabularyState::Uninitialized => panic!("vocabulary state not initialized"),
+        };
+        assert_eq!(state.visible.len(), 3);
+
+        state.set_search("猫".to_string());
+        let found: Vec<&str> = state
+            .visible
+            .iter()
+            .map(|&i| state.words[i].word.as_str())
+            .collect();
+        assert_eq!(found, vec!["猫", "猫头鹰"]);
+        state.select_next();
+        let owl = state.get_current().unwrap();
+        // suspended in Anki, known as external word
+        assert_eq!(owl.status, VocabStatus::AddedExternal);
+        assert!(owl.in_anki);
+        assert_eq!(owl.source(), "anki, manual");
+
+        state.set_search("鸟".to_string());
+        assert!(state.get_current().is_none());
+
+        // books stored after the tab was opened show up after a reload
+        state.set_search("猫".to_string());
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: vec!["猫".to_string(), "猫".to_string()],
+                sentences: vec![],
+            }],
+        };
+        db_books_insert(&db_connection.lock().unwrap(), "书", "", &book).unwrap();
+        assert!(state.books_containing("猫").is_empty());
+        state.reload().unwrap();
+        assert_eq!(state.books_containing("猫"), vec![("书", 2)]);
+        assert_eq!(state.visible.len(), 2);
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
 
 pub enum WordListState {
     List(ListOfWordLists),
-    Opened(OpenedWordList),
+    Opened(Box<OpenedWordList>),
 }
 
 impl WordListState {
@@ -83,7 +83,7 @@ impl ListOfWordLists {
                     metadata: wl_mdata.clone(),
                     words_per_chapter: chapters,
                 };
-                Ok(WordListState::Opened(OpenedWordList::new(wl)))
+                Ok(WordListState::Opened(Box::new(OpenedWordList::new(wl))))
             }
             None => Ok(WordListState::List(self)),
         }
@@ -293,17 +293,27 @@ impl WLChapterInfo {
         self.words_to_ignore
     }
 
-    pub fn get_words_to_learn(&self) -> Vec<&str> {
+    pub fn get_words_to_learn(&self) -> Vec<&TaggedWord> {
         self.chapter_words
             .tagged_words
             .iter()
-            .filter_map(|tw| {
-                if matches!(tw.category, Some(Category::Learn)) {
-                    Some(tw.word.as_str())
-                } else {
-                    None
-                }
-            })
+            .filter(|tw| matches!(tw.category, Some(Category::Learn)))
+            .collect()
+    }
+
+    pub fn get_words_to_not_learn(&self) -> Vec<&TaggedWord> {
+        self.chapter_words
+            .tagged_words
+            .iter()
+            .filter(|tw| matches!(tw.category, Some(Category::NotLearn)))
+            .collect()
+    }
+
+    pub fn get_words_to_ignore(&self) -> Vec<&TaggedWord> {
+        self.chapter_words
+            .tagged_words
+            .iter()
+            .filter(|tw| matches!(tw.category, Some(Category::Ignore)))
             .collect()
     }
 
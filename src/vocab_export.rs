@@ -0,0 +1,232 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+use crate::{
+    db::vocab::{VocabStatus, VocabWord},
+    extraction::{contains_hanzi, word_to_hanzi},
+    history::format_date,
+    report::csv_field,
+    vocabulary::{get_known_chars, CharOverrides},
+};
+
+/// Format of a vocabulary export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VocabExportFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl FromStr for VocabExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(VocabExportFormat::Csv),
+            "tsv" => Ok(VocabExportFormat::Tsv),
+            "json" => Ok(VocabExportFormat::Json),
+            _ => Err(anyhow!("unknown export format {}, use csv, tsv or json", s)),
+        }
+    }
+}
+
+/// A word (or known character) of the vocabulary export
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ExportEntry {
+    /// word or char
+    pub kind: &'static str,
+    pub text: String,
+    pub status: VocabStatus,
+    pub source: String,
+    /// date of the latest modification of the Anki notes containing the word
+    pub anki_modified: Option<String>,
+    /// date the word was added externally
+    pub added_at: Option<String>,
+}
+
+/// Entries of all words with one of the statuses (all words if None)
+///
+/// if chars is given as (known levels, character overrides), also includes the known characters:
+/// those of words known per these levels with the most advanced status of the words they occur in,
+/// and characters marked as known (as added external), but not those marked as unknown
+pub fn build_vocab_export(
+    words: &[VocabWord],
+    statuses: Option<&[VocabStatus]>,
+    chars: Option<(&[VocabStatus], &CharOverrides)>,
+) -> Vec<ExportEntry> {
+    let included = |status: &VocabStatus| statuses.is_none_or(|statuses| statuses.contains(status));
+    let mut entries: Vec<ExportEntry> = words
+        .iter()
+        .filter(|word| included(&word.status))
+        .map(|word| ExportEntry {
+            kind: "word",
+            text: word.word.clone(),
+            status: word.status,
+            source: word.source(),
+            anki_modified: word.anki_modified.map(format_date),
+            added_at: word
+                .external
+                .as_ref()
+                .and_then(|external| external.added_at)
+                .map(format_date),
+        })
+        .collect();
+    if let Some((known_levels, char_overrides)) = chars {
+        let known_words: Vec<&VocabWord> = words
+            .iter()
+            .filter(|word| word.status.is_known(known_levels))
+            .collect();
+        let known_chars = get_known_chars(
+            &known_words
+                .iter()
+                .map(|word| word.word.clone())
+                .collect::<HashSet<String>>(),
+            char_overrides,
+        );
+        let mut word_statuses: BTreeMap<&str, VocabStatus> = BTreeMap::new();
+        for word in known_words {
+            for hanzi in word_to_hanzi(&word.word) {
+                let status = word_statuses.entry(hanzi).or_insert(word.status);
+                *status = (*status).max(word.status);
+            }
+        }
+        let mut chars: Vec<(&str, VocabStatus, &str)> = known_chars
+            .iter()
+            .filter(|hanzi| contains_hanzi(hanzi))
+            .map(|hanzi| match word_statuses.get(hanzi.as_str()) {
+                Some(status) => (hanzi.as_str(), *status, ""),
+                None => (hanzi.as_str(), VocabStatus::AddedExternal, "marked"),
+            })
+            .collect();
+        chars.sort();
+        entries.extend(
+            chars
+                .into_iter()
+                .filter(|(_, status, _)| included(status))
+                .map(|(hanzi, status, source)| ExportEntry {
+                    kind: "char",
+                    text: hanzi.to_string(),
+                    status,
+                    source: source.to_string(),
+                    anki_modified: None,
+                    added_at: None,
+                }),
+        );
+    }
+    entries
+}
+
+pub fn render_vocab_export(entries: &[ExportEntry], format: VocabExportFormat) -> Result<String> {
+    let separator = match format {
+        VocabExportFormat::Json => return Ok(serde_json::to_string_pretty(entries)?),
+        VocabExportFormat::Csv => ",",
+        VocabExportFormat::Tsv => "\t",
+    };
+    let field = |value: &str| match format {
+        VocabExportFormat::Csv => csv_field(value),
+        _ => value.replace(['\t', '\n'], " "),
+    };
+    let mut out = [
+        "kind",
+        "text",
+        "status",
+        "source",
+        "anki_modified",
+        "added_at",
+    ]
+    .join(separator);
+    out.push('\n');
+    for entry in entries {
+        let fields = [
+            entry.kind.to_string(),
+            field(&entry.text),
+            entry.status.to_string(),
+            field(&entry.source),
+            entry.anki_modified.clone().unwrap_or_default(),
+            entry.added_at.clone().unwrap_or_default(),
+        ];
+        let _ = writeln!(out, "{}", fields.join(separator));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::{Duration, SystemTime};
+
+    use crate::db::vocab::{ExternalSource, ExternalWord};
+    use crate::vocab_export::*;
+
+    #[test]
+    fn export_words_and_known_chars() {
+        let day = SystemTime::UNIX_EPOCH + Duration::from_secs(86400);
+        let words = vec![
+            VocabWord {
+                word: "火车".to_string(),
+                status: VocabStatus::Mature,
+                in_anki: true,
+                anki_modified: Some(day),
+                external: None,
+            },
+            VocabWord {
+                word: "车站".to_string(),
+                status: VocabStatus::AddedExternal,
+                in_anki: false,
+                anki_modified: None,
+                external: Some(ExternalWord {
+                    word: "车站".to_string(),
+                    added_at: Some(day),
+                    source: ExternalSource::Manual,
+                    note: None,
+                }),
+            },
+            VocabWord {
+                word: "飞机".to_string(),
+                status: VocabStatus::New,
+                in_anki: true,
+                anki_modified: None,
+                external: None,
+            },
+        ];
+        let entries = build_vocab_export(
+            &words,
+            None,
+            Some((&[VocabStatus::Mature], &CharOverrides::default())),
+        );
+        let texts: Vec<&str> = entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["火车", "车站", "飞机", "火", "站", "车"]);
+        // 车 occurs in a mature and an external word
+        assert_eq!(entries[5].status, VocabStatus::AddedExternal);
+
+        let char_overrides = CharOverrides {
+            known: HashSet::from(["飞".to_string()]),
+            unknown: HashSet::from(["站".to_string()]),
+        };
+        let entries = build_vocab_export(
+            &words,
+            Some(&[VocabStatus::AddedExternal]),
+            Some((&[VocabStatus::Mature], &char_overrides)),
+        );
+        let chars: Vec<(&str, &str)> = entries
+            .iter()
+            .filter(|e| e.kind == "char")
+            .map(|e| (e.text.as_str(), e.source.as_str()))
+            .collect();
+        assert_eq!(chars, vec![("车", ""), ("飞", "marked")]);
+
+        let entries = build_vocab_export(&words, Some(&[VocabStatus::AddedExternal]), None);
+        let csv = render_vocab_export(&entries, VocabExportFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            "kind,text,status,source,anki_modified,added_at\n\
+             word,车站,added-external,manual,,1970-01-02\n"
+        );
+    }
+}
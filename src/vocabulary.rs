@@ -1,7 +1,11 @@
 use std::collections::HashSet;
 
 use crate::{
-    db::vocab::{db_words_select_all, VocabStatus},
+    config::get_config,
+    db::{
+        chars::db_char_overrides_select,
+        vocab::{db_words_select_all, VocabStatus},
+    },
     extraction::word_to_hanzi,
 };
 use anyhow::Result;
@@ -36,32 +40,92 @@ impl VocabularyInfo {
     }
 }
 
-pub fn get_known_chars(known_words: &HashSet<String>) -> HashSet<String> {
-    known_words
+/// Characters explicitly marked as known or unknown, taking precedence over
+/// the characters derived from known words
+#[derive(Clone, Default)]
+pub struct CharOverrides {
+    pub known: HashSet<String>,
+    pub unknown: HashSet<String>,
+}
+
+impl CharOverrides {
+    /// Apply the overrides to a set of known characters
+    pub fn apply(&self, known_chars: &mut HashSet<String>) {
+        known_chars.extend(self.known.iter().cloned());
+        known_chars.retain(|hanzi| !self.unknown.contains(hanzi));
+    }
+}
+
+/// What counts as known or ignored when analyzing a book
+pub struct VocabularyContext {
+    /// known words together with their known characters
+    pub known_words_and_chars: HashSet<String>,
+    /// characters explicitly marked as known/unknown
+    pub char_overrides: CharOverrides,
+    /// globally ignored words, left out of word lists
+    pub ignored_words: HashSet<String>,
+    /// whether ignored words are also left out of the unknown word counts
+    pub exclude_ignored: bool,
+}
+
+impl VocabularyContext {
+    /// Ignored words to leave out of unknown word counts, if configured so
+    pub fn ignored_for_counts(&self) -> Option<&HashSet<String>> {
+        self.exclude_ignored.then_some(&self.ignored_words)
+    }
+}
+
+/// Distinct characters of the text (sorted), leaving out whitespace, punctuation and latin letters
+pub fn split_chars(text: &str) -> Vec<&str> {
+    let mut chars: Vec<&str> = word_to_hanzi(text)
+        .into_iter()
+        .filter(|hanzi| hanzi.chars().all(|c| !c.is_ascii() && c.is_alphabetic()))
+        .collect();
+    chars.sort();
+    chars.dedup();
+    chars
+}
+
+pub fn get_known_chars(
+    known_words: &HashSet<String>,
+    char_overrides: &CharOverrides,
+) -> HashSet<String> {
+    let mut known_chars = known_words
         .iter()
         .flat_map(|w| word_to_hanzi(w))
         .map(|hanzi| hanzi.to_string())
-        .collect()
+        .collect();
+    char_overrides.apply(&mut known_chars);
+    known_chars
 }
 
-pub fn get_known_words_and_chars(known_words: HashSet<String>) -> HashSet<String> {
-    known_words
-        .union(&get_known_chars(&known_words))
+/// Known words together with their known characters,
+/// single characters marked as unknown are left out even if known as word
+pub fn get_known_words_and_chars(
+    known_words: HashSet<String>,
+    char_overrides: &CharOverrides,
+) -> HashSet<String> {
+    let mut known = known_words
+        .union(&get_known_chars(&known_words, char_overrides))
         .map(|s| s.to_string())
-        .collect::<HashSet<String>>()
+        .collect::<HashSet<String>>();
+    known.retain(|word| !char_overrides.unknown.contains(word));
+    known
 }
 
 pub fn get_vocab_stats(data_conn: &Connection) -> Result<VocabularyInfo> {
-    let vocabs = db_words_select_all(data_conn)?;
+    let known_levels = get_config().anki_levels.known;
+    let vocabs = db_words_select_all(data_conn, &known_levels)?;
 
     let mut words_active: HashSet<String> = HashSet::new();
     let mut words_inactive: HashSet<String> = HashSet::new();
     let mut words_external: HashSet<String> = HashSet::new();
+    // active are Anki words of known levels, inactive those of other levels
     for (word, status) in vocabs {
         match status {
-            VocabStatus::Active => &words_active.insert(word),
-            VocabStatus::Inactive => &words_inactive.insert(word),
             VocabStatus::AddedExternal => &words_external.insert(word),
+            status if status.is_known(&known_levels) => &words_active.insert(word),
+            _ => &words_inactive.insert(word),
         };
     }
 
@@ -91,6 +155,20 @@ pub fn get_vocab_stats(data_conn: &Connection) -> Result<VocabularyInfo> {
             }
         }
     }
+    // characters marked as known count as externally known,
+    // those marked as unknown as inactive
+    let char_overrides = db_char_overrides_select(data_conn)?;
+    for hanzi in &char_overrides.known {
+        if !chars_active.contains(hanzi.as_str()) {
+            chars_inactive.remove(hanzi.as_str());
+            chars_external.insert(hanzi);
+        }
+    }
+    for hanzi in &char_overrides.unknown {
+        if chars_active.remove(hanzi.as_str()) | chars_external.remove(hanzi.as_str()) {
+            chars_inactive.insert(hanzi);
+        }
+    }
 
     Ok(VocabularyInfo {
         words_known: words_active.len() + words_external.len(),
@@ -1,5 +1,8 @@
 use crate::config::{get_data_dir, tagging_socket_path, TAGGER_BIN};
+use crate::corpus::CorpusWord;
+use crate::examples::{get_example_sentences, MAX_EXAMPLE_SENTENCES};
 use crate::extraction::ExtractionItem;
+use crate::levels::WordLevel;
 use crate::segmentation::BookSegmentation;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
@@ -11,7 +14,7 @@ use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
-use crate::analysis::AnalysisQuery;
+use crate::analysis::{sort_by_local_frequency, AnalysisQuery};
 
 #[derive(Clone)]
 pub struct WordListMetadata {
@@ -20,17 +23,54 @@ pub struct WordListMetadata {
     pub author_name: String,
     pub create_time: SystemTime,
     pub analysis_query: AnalysisQuery,
+    /// Targeted word coverage in percent, if list was created from a coverage plan
+    pub coverage_target: Option<u64>,
+    /// Whether list was created from the book's unknown characters
+    pub character_focused: bool,
+    /// Whether list was created from all books instead of a single one
+    pub library_wide: bool,
+}
+
+impl WordListMetadata {
+    /// Metadata for a new (not yet saved) word list
+    pub fn new(book_name: &str, author_name: &str, analysis_query: AnalysisQuery) -> Self {
+        Self {
+            id: -1,
+            book_name: book_name.to_string(),
+            author_name: author_name.to_string(),
+            create_time: SystemTime::now(),
+            analysis_query,
+            coverage_target: None,
+            character_focused: false,
+            library_wide: false,
+        }
+    }
+
+    pub fn filter_description(&self) -> String {
+        if self.library_wide {
+            return "library".to_string();
+        }
+        if self.character_focused {
+            return "chars".to_string();
+        }
+        match self.coverage_target {
+            Some(target) => format!("cov{}", target),
+            None => self.analysis_query.to_string(),
+        }
+    }
 }
 
 impl Display for WordListMetadata {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.author_name.is_empty() {
-            write!(f, "{}_{}", self.book_name, self.analysis_query)
+            write!(f, "{}_{}", self.book_name, self.filter_description())
         } else {
             write!(
                 f,
                 "{}-{}_{}",
-                self.author_name, self.book_name, self.analysis_query
+                self.author_name,
+                self.book_name,
+                self.filter_description()
             )
         }
     }
@@ -51,6 +91,10 @@ pub struct ChapterWords {
 pub struct TaggedWord {
     pub word: String,
     pub category: Option<Category>,
+    #[serde(default)]
+    pub examples: Vec<String>,
+    #[serde(default)]
+    pub level: WordLevel,
 }
 
 impl TaggedWord {
@@ -58,6 +102,8 @@ impl TaggedWord {
         Self {
             word: word.to_string(),
             category: None,
+            examples: vec![],
+            level: WordLevel::default(),
         }
     }
 
@@ -78,13 +124,25 @@ pub enum Category {
 }
 
 // construct word list from book and analysis query/result
+// words of each chapter are ranked by their frequency within that chapter
+// and come with example sentences from the book, globally ignored words are left out
 pub fn construct_word_list(
     book: &BookSegmentation,
-    title: &str,
-    author: &str,
-    analysis_query: AnalysisQuery,
+    metadata: WordListMetadata,
     unknown_words_to_save: &HashSet<&ExtractionItem>,
+    known_words: &HashSet<String>,
+    ignored_words: &HashSet<String>,
 ) -> WordList {
+    let unknown_words_to_save: HashSet<&ExtractionItem> = unknown_words_to_save
+        .iter()
+        .copied()
+        .filter(|item| !ignored_words.contains(&item.word))
+        .collect();
+    let words: HashSet<&str> = unknown_words_to_save
+        .iter()
+        .map(|item| item.word.as_str())
+        .collect();
+    let mut examples = get_example_sentences(book, &words, known_words, MAX_EXAMPLE_SENTENCES);
     let chapter_titles: Vec<&str> = book.chapter_cuts.iter().map(|c| c.title.as_str()).collect();
     let mut chapter_vocabulary: HashMap<&str, HashSet<&ExtractionItem>> = chapter_titles
         .iter()
@@ -98,12 +156,23 @@ pub fn construct_word_list(
     }
     let words_per_chapter: Vec<ChapterWords> = chapter_titles
         .iter()
-        .map(|chapter_name| {
-            let tagged_words: Vec<TaggedWord> = chapter_vocabulary
+        .enumerate()
+        .map(|(i, chapter_name)| {
+            let mut items: Vec<&ExtractionItem> = chapter_vocabulary
                 .get(chapter_name)
                 .unwrap()
                 .iter()
-                .map(|item| TaggedWord::new(item.word.as_str()))
+                .copied()
+                .collect();
+            sort_by_local_frequency(&mut items, i..i + 1);
+            let tagged_words: Vec<TaggedWord> = items
+                .iter()
+                .map(|item| TaggedWord {
+                    word: item.word.clone(),
+                    category: None,
+                    examples: examples.remove(&item.word).unwrap_or_default(),
+                    level: item.level,
+                })
                 .collect();
             ChapterWords {
                 chapter_name: chapter_name.to_string(),
@@ -111,13 +180,32 @@ pub fn construct_word_list(
             }
         })
         .collect();
-    let metadata = WordListMetadata {
-        id: -1,
-        book_name: title.to_string(),
-        author_name: author.to_string(),
-        create_time: SystemTime::now(),
-        analysis_query,
-    };
+    WordList {
+        metadata,
+        words_per_chapter,
+    }
+}
+
+/// Amount of words per chapter of library word lists
+pub const LIBRARY_WORD_LIST_CHUNK_SIZE: usize = 100;
+
+// construct word list from library recommendations,
+// words keep their ranking and are split into chapters of fixed size
+pub fn construct_library_word_list(recommendations: &[CorpusWord]) -> WordList {
+    let mut metadata = WordListMetadata::new("Library", "", AnalysisQuery::new(1));
+    metadata.library_wide = true;
+    let words_per_chapter = recommendations
+        .chunks(LIBRARY_WORD_LIST_CHUNK_SIZE)
+        .enumerate()
+        .map(|(i, chunk)| ChapterWords {
+            chapter_name: format!(
+                "{}-{}",
+                i * LIBRARY_WORD_LIST_CHUNK_SIZE + 1,
+                i * LIBRARY_WORD_LIST_CHUNK_SIZE + chunk.len()
+            ),
+            tagged_words: chunk.iter().map(|w| TaggedWord::new(&w.word)).collect(),
+        })
+        .collect();
     WordList {
         metadata,
         words_per_chapter,
@@ -131,7 +219,7 @@ pub fn tag_words(words: &mut Vec<TaggedWord>) {
     }
     let listener = UnixListener::bind(socket).expect("could not bind to socket");
 
-    let _c = Command::new(TAGGER_BIN)
+    let mut tagger = Command::new(TAGGER_BIN)
         .env("DATA_DIR", get_data_dir())
         .spawn()
         .expect("could not spawn han-shaixuan");
@@ -157,5 +245,47 @@ pub fn tag_words(words: &mut Vec<TaggedWord>) {
 
     let tagged_words: Vec<TaggedWord> =
         serde_json::from_reader(stream).expect("could not read/deserialize from stream");
+    tagger.wait().expect("han-shaixuan did not exit");
     *words = tagged_words;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::analysis::AnalysisQuery;
+    use crate::extraction::{extract_vocab_from_segmented, ExtractionItem};
+    use crate::segmentation::{BookSegmentation, ChapterSegmentation};
+    use crate::word_lists::{construct_word_list, WordListMetadata};
+
+    #[test]
+    fn leave_out_ignored_words() {
+        let book = BookSegmentation {
+            title: "书".to_string(),
+            chapter_cuts: vec![ChapterSegmentation {
+                title: "一".to_string(),
+                cut: ["猫", "狗", "狗", "鱼"]
+                    .iter()
+                    .map(|w| w.to_string())
+                    .collect(),
+                sentences: vec![],
+            }],
+        };
+        let extraction_res = extract_vocab_from_segmented(book.clone());
+        let unknown: HashSet<&ExtractionItem> = extraction_res.vocabulary.iter().collect();
+        let ignored = HashSet::from(["猫".to_string()]);
+        let word_list = construct_word_list(
+            &book,
+            WordListMetadata::new("书", "", AnalysisQuery::new(1)),
+            &unknown,
+            &HashSet::new(),
+            &ignored,
+        );
+        let words: Vec<&str> = word_list.words_per_chapter[0]
+            .tagged_words
+            .iter()
+            .map(|tw| tw.word.as_str())
+            .collect();
+        assert_eq!(words, vec!["狗", "鱼"]);
+    }
+}